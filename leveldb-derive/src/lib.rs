@@ -0,0 +1,239 @@
+//! `#[derive(Serializable)]` for leveldb keys.
+//!
+//! The derived encoding concatenates the fields in declaration order, each
+//! with its order-preserving `TupleElement` encoding, so keys sort the same
+//! way as with a derived `Ord`. Enums are prefixed with the index of their
+//! variant.
+//!
+//! Fields accept the following attributes:
+//!
+//! * `#[serializable(descending)]` sorts the field in descending order
+//! * `#[serializable(width = N)]` stores an integer field in `N` bytes,
+//!   where `N` is at most 8. Encoding a value that doesn't fit panics.
+//!
+//! Re-exported by `leveldb` as `leveldb::database::serializable::Serializable`
+//! when its `derive` feature is enabled.
+
+extern crate proc_macro;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, LitInt};
+
+/// Derive `leveldb::database::serializable::Serializable`.
+#[proc_macro_derive(Serializable, attributes(serializable))]
+pub fn derive_serializable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    descending: bool,
+    width: Option<usize>,
+}
+
+fn field_options(field: &syn::Field) -> Result<FieldOptions, Error> {
+    let mut options = FieldOptions::default();
+    for attr in &field.attrs {
+        if !attr.path().is_ident("serializable") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("descending") {
+                options.descending = true;
+                Ok(())
+            } else if meta.path.is_ident("width") {
+                let width: LitInt = meta.value()?.parse()?;
+                let value = width.base10_parse::<usize>()?;
+                if !(1..=8).contains(&value) {
+                    return Err(Error::new(width.span(), "width must be between 1 and 8"));
+                }
+                options.width = Some(value);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported serializable attribute"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+/// Code appending the encoding of `value`, a reference to the field, to `out`.
+fn encode_field(field: &syn::Field, value: TokenStream) -> Result<TokenStream, Error> {
+    let options = field_options(field)?;
+    let encode = match options.width {
+        Some(width) => quote! {
+            ::leveldb::database::derive_support::encode_width(*#value, #width, out)
+        },
+        None => quote! {
+            ::leveldb::database::tuple::TupleElement::encode_into(#value, out)
+        },
+    };
+    Ok(if options.descending {
+        quote! {
+            ::leveldb::database::derive_support::encode_descending(out, |out| #encode);
+        }
+    } else {
+        quote! { #encode; }
+    })
+}
+
+/// An expression decoding a field from `input`.
+fn decode_field(field: &syn::Field) -> Result<TokenStream, Error> {
+    let options = field_options(field)?;
+    let ty = &field.ty;
+    let decode = match options.width {
+        Some(width) => quote! {
+            ::leveldb::database::derive_support::decode_width::<#ty>(input, #width)
+        },
+        None => quote! {
+            <#ty as ::leveldb::database::tuple::TupleElement>::decode_from(input)
+        },
+    };
+    Ok(if options.descending {
+        quote! {
+            ::leveldb::database::derive_support::decode_descending(input, |input| #decode)?
+        }
+    } else {
+        quote! { #decode? }
+    })
+}
+
+/// The bindings of the fields of a pattern, and the pattern itself.
+fn bindings(fields: &Fields) -> (Vec<syn::Ident>, TokenStream) {
+    let names: Vec<syn::Ident> = (0..fields.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect();
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let field_names = named.named.iter().map(|f| &f.ident);
+            quote! { { #(#field_names: #names),* } }
+        }
+        Fields::Unnamed(_) => quote! { ( #(#names),* ) },
+        Fields::Unit => quote! {},
+    };
+    (names, pattern)
+}
+
+/// An expression constructing `path` from fields decoded in order.
+fn construct(path: TokenStream, fields: &Fields) -> Result<TokenStream, Error> {
+    let decoded = fields
+        .iter()
+        .map(decode_field)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(match fields {
+        Fields::Named(named) => {
+            let field_names = named.named.iter().map(|f| &f.ident);
+            quote! { #path { #(#field_names: #decoded),* } }
+        }
+        Fields::Unnamed(_) => quote! { #path ( #(#decoded),* ) },
+        Fields::Unit => path,
+    })
+}
+
+fn encode_fields(fields: &Fields, names: &[syn::Ident]) -> Result<TokenStream, Error> {
+    let encoded = fields
+        .iter()
+        .zip(names)
+        .map(|(field, name)| encode_field(field, quote! { #name }))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(quote! { #(#encoded)* })
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream, Error> {
+    let name = &input.ident;
+    let (encode, decode) = match input.data {
+        Data::Struct(ref data) => {
+            let (names, pattern) = bindings(&data.fields);
+            let encoded = encode_fields(&data.fields, &names)?;
+            let constructed = construct(quote! { #name }, &data.fields)?;
+            (
+                quote! {
+                    let #name #pattern = self;
+                    #encoded
+                },
+                quote! { Ok(#constructed) },
+            )
+        }
+        Data::Enum(ref data) => {
+            if data.variants.len() > usize::from(u16::MAX) + 1 {
+                return Err(Error::new_spanned(&input.ident, "too many variants"));
+            }
+            let index_type = if data.variants.len() <= 256 {
+                quote! { u8 }
+            } else {
+                quote! { u16 }
+            };
+            let mut encode_arms = vec![];
+            let mut decode_arms = vec![];
+            for (index, variant) in data.variants.iter().enumerate() {
+                let variant_name = &variant.ident;
+                let (names, pattern) = bindings(&variant.fields);
+                let encoded = encode_fields(&variant.fields, &names)?;
+                let index = LitInt::new(&format!("{}{}", index, index_type), variant.ident.span());
+                encode_arms.push(quote! {
+                    #name::#variant_name #pattern => {
+                        ::leveldb::database::tuple::TupleElement::encode_into(&#index, out);
+                        #encoded
+                    }
+                });
+                let constructed = construct(quote! { #name::#variant_name }, &variant.fields)?;
+                decode_arms.push(quote! { #index => Ok(#constructed), });
+            }
+            (
+                quote! {
+                    match self {
+                        #(#encode_arms)*
+                    }
+                },
+                quote! {
+                    let index =
+                        <#index_type as ::leveldb::database::tuple::TupleElement>::decode_from(input)?;
+                    match index {
+                        #(#decode_arms)*
+                        _ => Err(::leveldb::database::derive_support::invalid_variant(index as usize)),
+                    }
+                },
+            )
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Serializable can't be derived for unions",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(parse_quote!(::leveldb::database::tuple::TupleElement));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::leveldb::database::serializable::Serializable for #name #ty_generics
+            #where_clause
+        {
+            fn from_u8(key: &[u8]) -> Self {
+                <Self as ::leveldb::database::serializable::Serializable>::try_from_u8(key)
+                    .unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            fn as_u8(&self) -> Vec<u8> {
+                let mut key = Vec::new();
+                let out = &mut key;
+                #encode
+                key
+            }
+
+            fn try_from_u8(key: &[u8]) -> Result<Self, ::leveldb::error::Error> {
+                ::leveldb::database::derive_support::decode_all(key, |input| { #decode })
+            }
+        }
+    })
+}
@@ -139,7 +139,7 @@ extern "C" {
     pub fn leveldb_iter_prev(it: *mut leveldb_iterator_t);
     pub fn leveldb_iter_key(it: *const leveldb_iterator_t, klen: *const size_t) -> *const c_char;
     pub fn leveldb_iter_value(it: *const leveldb_iterator_t, vlen: *const size_t) -> *const c_char;
-    pub fn leveldb_iter_get_error(it: *const leveldb_iterator_t, errptr: *const *const c_char);
+    pub fn leveldb_iter_get_error(it: *const leveldb_iterator_t, errptr: *mut *mut c_char);
 
     // Write batch
     pub fn leveldb_writebatch_create() -> *mut leveldb_writebatch_t;
@@ -200,22 +200,25 @@ extern "C" {
     pub fn leveldb_comparator_destroy(c: *mut leveldb_comparator_t);
 
     // Filter policy
-    //pub leveldb_filterpolicy_create(state: *mut c_void, /* TODO */) -> *mut leveldb_filterpolicy_t;
-    /*
-        extern leveldb_filterpolicy_t* leveldb_filterpolicy_create(
-            void* state,
-            void (*destructor)(void*),
-            char* (*create_filter)(
-                void*,
-                const char* const* key_array, const size_t* key_length_array,
-                int num_keys,
-                size_t* filter_length),
-            unsigned char (*key_may_match)(
-                void*,
-                const char* key, size_t length,
-                const char* filter, size_t filter_length),
-            const char* (*name)(void*));
-    */
+    pub fn leveldb_filterpolicy_create(
+        state: *mut c_void,
+        destructor: extern "C" fn(*mut c_void),
+        create_filter: extern "C" fn(
+            *mut c_void,
+            *const *const c_char,
+            *const size_t,
+            c_int,
+            *mut size_t,
+        ) -> *mut c_char,
+        key_may_match: extern "C" fn(
+            *mut c_void,
+            *const c_char,
+            size_t,
+            *const c_char,
+            size_t,
+        ) -> c_uchar,
+        name: extern "C" fn(*mut c_void) -> *const c_char,
+    ) -> *mut leveldb_filterpolicy_t;
     pub fn leveldb_filterpolicy_destroy(p: *mut leveldb_filterpolicy_t);
     pub fn leveldb_filterpolicy_create_bloom(bits_per_key: c_int) -> *mut leveldb_filterpolicy_t;
 
@@ -0,0 +1,216 @@
+//! Async access to leveldb, enabled by the `tokio` feature.
+//!
+//! leveldb blocks the calling thread on disk access and on synced writes.
+//! `AsyncDatabase` runs every operation on tokio's blocking thread pool, so
+//! it can be awaited from async code without stalling the executor.
+//!
+//! Range iteration is exposed as a `Stream`, which reads entries in pages
+//! off-thread.
+use super::batch::{Batch, Writebatch};
+use super::compaction::Compaction;
+use super::error::{Error, ErrorKind};
+use super::iterator::ArcIterator;
+use super::kv::KV;
+use super::options::{ReadOptions, WriteOptions};
+use super::serializable::Serializable;
+use super::Database;
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::ops::RangeBounds;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::task::{self, JoinError, JoinHandle};
+
+/// A database whose operations run on tokio's blocking thread pool.
+///
+/// Cloning an `AsyncDatabase` is cheap, all clones share the same database.
+pub struct AsyncDatabase<K: Serializable> {
+    database: Arc<Database<K>>,
+}
+
+/// A stream over the entries of a range, read in pages on the blocking
+/// thread pool.
+///
+/// If leveldb fails while iterating, the error is yielded as the final item.
+pub struct RangeStream<K: Serializable + 'static> {
+    iter: Option<ArcIterator<K>>,
+    page: VecDeque<Result<(K, Vec<u8>), Error>>,
+    pending: Option<JoinHandle<Page<K>>>,
+    page_size: usize,
+}
+
+type Page<K> = (ArcIterator<K>, Vec<Result<(K, Vec<u8>), Error>>);
+
+fn join_error(error: JoinError) -> Error {
+    Error::with_kind(
+        ErrorKind::Other,
+        format!("blocking leveldb task failed: {}", error),
+    )
+}
+
+impl<K: Serializable + Send + Sync + 'static> AsyncDatabase<K> {
+    /// Wrap `database` for async use.
+    pub fn new(database: Database<K>) -> AsyncDatabase<K> {
+        AsyncDatabase::from_arc(Arc::new(database))
+    }
+
+    /// Wrap a shared `database` for async use.
+    pub fn from_arc(database: Arc<Database<K>>) -> AsyncDatabase<K> {
+        AsyncDatabase { database: database }
+    }
+
+    /// The wrapped database, for synchronous access.
+    pub fn database(&self) -> &Arc<Database<K>> {
+        &self.database
+    }
+
+    async fn run<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database<K>) -> Result<T, Error> + Send + 'static,
+    {
+        let database = self.database.clone();
+        task::spawn_blocking(move || f(&database))
+            .await
+            .map_err(join_error)?
+    }
+
+    /// get a value from the database.
+    pub async fn get(
+        &self,
+        options: ReadOptions<'static, K>,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.run(move |database| database.get(options, key)).await
+    }
+
+    /// put a binary value into the database.
+    ///
+    /// If the key is already present in the database, it will be overwritten.
+    pub async fn put(&self, options: WriteOptions, key: K, value: &[u8]) -> Result<(), Error> {
+        let value = value.to_vec();
+        self.run(move |database| database.put(options, key, &value))
+            .await
+    }
+
+    /// delete a value from the database.
+    pub async fn delete(&self, options: WriteOptions, key: K) -> Result<(), Error> {
+        self.run(move |database| database.delete(options, key))
+            .await
+    }
+
+    /// Write a batch to the database, ensuring success for all items or an error
+    ///
+    /// The batch is handed back once written, so it can be reused.
+    pub async fn write(
+        &self,
+        options: WriteOptions,
+        batch: Writebatch<K>,
+    ) -> Result<Writebatch<K>, Error> {
+        self.run(move |database| database.write(options, &batch).map(|_| batch))
+            .await
+    }
+
+    /// Compact the keys between `start` and `limit`, or from the first or up
+    /// to the last key if a bound is `None`.
+    pub async fn compact(&self, start: Option<K>, limit: Option<K>) -> Result<(), Error> {
+        self.run(move |database| {
+            database.compact_range(start.as_ref(), limit.as_ref());
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stream the entries within `range`, reading `page_size` entries at a
+    /// time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_size` is zero.
+    pub fn range_stream<R: RangeBounds<K>>(
+        &self,
+        options: ReadOptions<'static, K>,
+        range: R,
+        page_size: usize,
+    ) -> RangeStream<K> {
+        assert!(page_size > 0, "page_size must be positive");
+        RangeStream {
+            iter: Some(ArcIterator::range(self.database.clone(), options, range)),
+            page: VecDeque::new(),
+            pending: None,
+            page_size: page_size,
+        }
+    }
+}
+
+impl<K: Serializable> Clone for AsyncDatabase<K> {
+    fn clone(&self) -> AsyncDatabase<K> {
+        AsyncDatabase {
+            database: self.database.clone(),
+        }
+    }
+}
+
+// RangeStream never pins its fields, so it can move while polled.
+impl<K: Serializable> Unpin for RangeStream<K> {}
+
+impl<K: Serializable + Send + Sync + 'static> RangeStream<K> {
+    fn read_page(mut iter: ArcIterator<K>, page_size: usize) -> Page<K> {
+        let mut entries = Vec::with_capacity(page_size);
+        while entries.len() < page_size {
+            match iter.try_next() {
+                Ok(Some(entry)) => entries.push(Ok(entry)),
+                Ok(None) => break,
+                Err(e) => {
+                    entries.push(Err(e));
+                    break;
+                }
+            }
+        }
+        (iter, entries)
+    }
+}
+
+impl<K: Serializable + Send + Sync + 'static> Stream for RangeStream<K> {
+    type Item = Result<(K, Vec<u8>), Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(entry) = this.page.pop_front() {
+                return Poll::Ready(Some(entry));
+            }
+            if let Some(ref mut pending) = this.pending {
+                let result = match Pin::new(pending).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(result) => result,
+                };
+                this.pending = None;
+                match result {
+                    Ok((iter, entries)) => {
+                        // a short page means the iterator is exhausted
+                        let last = entries.len() < this.page_size
+                            || entries.last().is_some_and(|entry| entry.is_err());
+                        if !last {
+                            this.iter = Some(iter);
+                        }
+                        this.page.extend(entries);
+                    }
+                    Err(e) => return Poll::Ready(Some(Err(join_error(e)))),
+                }
+                continue;
+            }
+            match this.iter.take() {
+                Some(iter) => {
+                    let page_size = this.page_size;
+                    this.pending = Some(task::spawn_blocking(move || {
+                        RangeStream::read_page(iter, page_size)
+                    }));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
@@ -1,13 +1,14 @@
 //! Module providing write batches
 
-use super::error::Error;
+use super::error::{Error, ErrorKind};
 use super::Database;
 use crate::binding::*;
-use crate::database::serializable::from_u8;
+use crate::database::serializable::try_from_u8;
 use crate::database::serializable::Serializable;
 use crate::options::{c_writeoptions, WriteOptions};
 use libc::{c_char, c_void, size_t};
 use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
 use std::slice;
 
@@ -24,6 +25,11 @@ impl Drop for RawWritebatch {
     }
 }
 
+// SAFETY: a leveldb write batch is a plain buffer with no ties to the
+// thread that created it. It is owned by a single `Writebatch`, which only
+// mutates it through `&mut self`.
+unsafe impl Send for RawWritebatch {}
+
 #[allow(missing_docs)]
 pub struct Writebatch<K: Serializable> {
     #[allow(dead_code)]
@@ -39,6 +45,15 @@ pub trait Batch<K: Serializable> {
 
 impl<K: Serializable> Batch<K> for Database<K> {
     fn write(&self, options: WriteOptions, batch: &Writebatch<K>) -> Result<(), Error> {
+        self.check_poisoned()?;
+        // the view of a namespace writes a copy with the prefixed keys
+        let prefixed;
+        let batch = if self.prefix.is_empty() {
+            batch
+        } else {
+            prefixed = batch.with_prefix(&self.prefix);
+            &prefixed
+        };
         unsafe {
             let mut error = ptr::null_mut();
             let c_writeoptions = c_writeoptions(options);
@@ -52,7 +67,8 @@ impl<K: Serializable> Batch<K> for Database<K> {
             leveldb_writeoptions_destroy(c_writeoptions);
 
             if error == ptr::null_mut() {
-                Ok(())
+                // the comparator may have panicked during the write
+                self.check_poisoned()
             } else {
                 Err(Error::new_from_char(error))
             }
@@ -105,17 +121,91 @@ impl<K: Serializable> Writebatch<K> {
     }
 
     /// Iterate over the writebatch, returning the resulting iterator
-    pub fn iterate<T: WritebatchIterator<K = K>>(&mut self, iterator: Box<T>) -> Box<T> {
+    ///
+    /// If a key in the batch can't be decoded or the iterator panics, the
+    /// remaining items are skipped and an error is returned.
+    pub fn iterate<T: WritebatchIterator<K = K>>(
+        &mut self,
+        iterator: Box<T>,
+    ) -> Result<Box<T>, Error> {
+        let mut state = CallbackState {
+            iterator: iterator,
+            error: None,
+        };
         unsafe {
-            let iter = Box::into_raw(iterator);
             leveldb_writebatch_iterate(
                 self.writebatch.ptr,
-                iter as *mut c_void,
+                &mut state as *mut CallbackState<T> as *mut c_void,
                 put_callback::<K, T>,
                 deleted_callback::<K, T>,
             );
-            Box::from_raw(iter)
         }
+        match state.error {
+            None => Ok(state.iterator),
+            Some(error) => Err(error),
+        }
+    }
+
+    /// A copy of this batch with `prefix` prepended to all keys.
+    fn with_prefix(&self, prefix: &[u8]) -> Writebatch<K> {
+        let copy = Writebatch::new();
+        let mut state = PrefixState {
+            prefix: prefix,
+            target: copy.writebatch.ptr,
+        };
+        unsafe {
+            leveldb_writebatch_iterate(
+                self.writebatch.ptr,
+                &mut state as *mut PrefixState as *mut c_void,
+                prefix_put_callback,
+                prefix_deleted_callback,
+            );
+        }
+        copy
+    }
+}
+
+struct PrefixState<'a> {
+    prefix: &'a [u8],
+    target: *mut leveldb_writebatch_t,
+}
+
+impl<'a> PrefixState<'a> {
+    unsafe fn key(&self, key: *const c_char, keylen: size_t) -> Vec<u8> {
+        let key_slice = slice::from_raw_parts::<u8>(key as *const u8, keylen as usize);
+        [self.prefix, key_slice].concat()
+    }
+}
+
+extern "C" fn prefix_put_callback(
+    state: *mut c_void,
+    key: *const c_char,
+    keylen: size_t,
+    val: *const c_char,
+    vallen: size_t,
+) {
+    unsafe {
+        let state: &PrefixState = &*(state as *const PrefixState);
+        let key = state.key(key, keylen);
+        leveldb_writebatch_put(
+            state.target,
+            key.as_ptr() as *const c_char,
+            key.len() as size_t,
+            val,
+            vallen,
+        );
+    }
+}
+
+extern "C" fn prefix_deleted_callback(state: *mut c_void, key: *const c_char, keylen: size_t) {
+    unsafe {
+        let state: &PrefixState = &*(state as *const PrefixState);
+        let key = state.key(key, keylen);
+        leveldb_writebatch_delete(
+            state.target,
+            key.as_ptr() as *const c_char,
+            key.len() as size_t,
+        );
     }
 }
 
@@ -131,6 +221,29 @@ pub trait WritebatchIterator {
     fn deleted(&mut self, key: Self::K);
 }
 
+struct CallbackState<T> {
+    iterator: Box<T>,
+    error: Option<Error>,
+}
+
+impl<T> CallbackState<T> {
+    // Panics must not unwind into leveldb, so they are turned into an error,
+    // whether they come from decoding the key or from the iterator
+    fn call<F: FnOnce(&mut T) -> Result<(), Error>>(&mut self, f: F) {
+        let iterator = &mut self.iterator;
+        match panic::catch_unwind(AssertUnwindSafe(|| f(iterator))) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => self.error = Some(e),
+            Err(_) => {
+                self.error = Some(Error::with_kind(
+                    ErrorKind::Poisoned,
+                    "writebatch iterator panicked".to_string(),
+                ))
+            }
+        }
+    }
+}
+
 extern "C" fn put_callback<K: Serializable, T: WritebatchIterator<K = K>>(
     state: *mut c_void,
     key: *const c_char,
@@ -139,11 +252,17 @@ extern "C" fn put_callback<K: Serializable, T: WritebatchIterator<K = K>>(
     vallen: size_t,
 ) {
     unsafe {
-        let iter: &mut T = &mut *(state as *mut T);
+        let state: &mut CallbackState<T> = &mut *(state as *mut CallbackState<T>);
+        if state.error.is_some() {
+            return;
+        }
         let key_slice = slice::from_raw_parts::<u8>(key as *const u8, keylen as usize);
         let val_slice = slice::from_raw_parts::<u8>(val as *const u8, vallen as usize);
-        let k = from_u8::<<T as WritebatchIterator>::K>(key_slice);
-        iter.put(k, val_slice);
+        state.call(|iterator| {
+            let k = try_from_u8::<<T as WritebatchIterator>::K>(key_slice)?;
+            iterator.put(k, val_slice);
+            Ok(())
+        });
     }
 }
 
@@ -153,9 +272,15 @@ extern "C" fn deleted_callback<K: Serializable, T: WritebatchIterator<K = K>>(
     keylen: size_t,
 ) {
     unsafe {
-        let iter: &mut T = &mut *(state as *mut T);
+        let state: &mut CallbackState<T> = &mut *(state as *mut CallbackState<T>);
+        if state.error.is_some() {
+            return;
+        }
         let key_slice = slice::from_raw_parts::<u8>(key as *const u8, keylen as usize);
-        let k = from_u8::<<T as WritebatchIterator>::K>(key_slice);
-        iter.deleted(k);
+        state.call(|iterator| {
+            let k = try_from_u8::<<T as WritebatchIterator>::K>(key_slice)?;
+            iterator.deleted(k);
+            Ok(())
+        });
     }
 }
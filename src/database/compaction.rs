@@ -1,24 +1,60 @@
 //! Compaction
 use super::serializable::Serializable;
-use super::Database;
+use super::{prefix_successor, Database};
 use crate::binding::leveldb_compact_range;
 use libc::{c_char, size_t};
+use std::ptr;
 
 pub trait Compaction<'a, K: Serializable + 'a> {
-    fn compact(&self, start: &'a K, limit: &'a K);
+    /// Compact the key range from `start` to `limit`, both inclusive.
+    fn compact(&self, start: &'a K, limit: &'a K) {
+        self.compact_range(Some(start), Some(limit))
+    }
+
+    /// Compact the key range from `start` to `limit`, both inclusive.
+    ///
+    /// `None` leaves the respective end unbounded, so `start: None`
+    /// compacts from the beginning and `limit: None` until the end of
+    /// the keyspace.
+    fn compact_range(&self, start: Option<&'a K>, limit: Option<&'a K>);
+
+    /// Compact the whole database.
+    fn compact_all(&self) {
+        self.compact_range(None, None)
+    }
 }
 
 impl<'a, K: Serializable + 'a> Compaction<'a, K> for Database<K> {
-    fn compact(&self, start: &'a K, limit: &'a K) {
+    fn compact_range(&self, start: Option<&'a K>, limit: Option<&'a K>) {
+        if self.is_poisoned() {
+            return;
+        }
+        // the unbounded ends of a namespace are the ends of its prefix
+        let start = match start {
+            Some(k) => Some(self.encode_key(k)),
+            None if !self.prefix.is_empty() => Some(self.prefix.clone()),
+            None => None,
+        };
+        let limit = match limit {
+            Some(k) => Some(self.encode_key(k)),
+            None => prefix_successor(&self.prefix),
+        };
+        // leveldb treats a NULL pointer as an unbounded end
+        let (start_ptr, start_len) = match start {
+            Some(ref k) => (k.as_ptr() as *const c_char, k.len() as size_t),
+            None => (ptr::null(), 0),
+        };
+        let (limit_ptr, limit_len) = match limit {
+            Some(ref k) => (k.as_ptr() as *const c_char, k.len() as size_t),
+            None => (ptr::null(), 0),
+        };
         unsafe {
-            let start = &start.as_u8();
-            let limit = &limit.as_u8();
             leveldb_compact_range(
                 self.database.ptr,
-                start.as_ptr() as *mut c_char,
-                start.len() as size_t,
-                limit.as_ptr() as *mut c_char,
-                limit.len() as size_t,
+                start_ptr,
+                start_len,
+                limit_ptr,
+                limit_len,
             )
         }
     }
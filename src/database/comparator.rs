@@ -4,25 +4,36 @@
 //! Comparators allow to override this comparison.
 //! The ordering of keys introduced by the compartor influences iteration order.
 //! Databases written with one Comparator cannot be opened with another.
+//!
+//! `Comparator` compares deserialized keys, `RawComparator` compares the
+//! encoded bytes and is cheaper on leveldb's hot paths.
 use crate::binding::*;
-use crate::database::serializable::from_u8;
+use crate::database::serializable::try_from_u8;
 use crate::database::serializable::Serializable;
 use libc::{c_char, c_void, size_t};
 use std::cmp::Ordering;
+use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
 use std::slice;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
 
 /// A comparator has two important functions:
 ///
 /// * the name function returns a fixed name to detect errors when
-///   opening databases with a different name
+///   opening databases with a different comparator. Opening such a database
+///   fails with `ErrorKind::ComparatorMismatch`.
 /// * The comparison implementation
-pub trait Comparator {
+///
+/// leveldb calls the comparator from its background compaction thread and
+/// from all threads using the database, so it must be `Send + Sync`.
+pub trait Comparator: Send + Sync {
     /// The type that the comparator compares.
     type K: Serializable;
 
     /// Return the name of the Comparator
-    fn name(&self) -> *const c_char;
+    fn name(&self) -> &CStr;
     /// compare two keys. This must implement a total ordering.
     fn compare(&self, a: &Self::K, b: &Self::K) -> Ordering;
     /// whether the comparator is the `DefaultComparator`
@@ -33,16 +44,63 @@ pub trait Comparator {
 
 /// OrdComparator is a comparator comparing Keys that implement `Ord`
 pub struct OrdComparator<K: Serializable + Ord> {
-    name: String,
-    marker: PhantomData<K>,
+    name: CString,
+    // no K is stored, so the comparator is Send + Sync for all keys
+    marker: PhantomData<fn() -> K>,
 }
 
 impl<K: Serializable + Ord> OrdComparator<K> {
     /// Create a new OrdComparator
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains a NUL byte.
     pub fn new(name: &str) -> OrdComparator<K> {
         OrdComparator {
             marker: PhantomData,
-            name: name.to_string(),
+            name: CString::new(name).expect("comparator name must not contain NUL bytes"),
+        }
+    }
+}
+
+/// FnComparator is a comparator comparing keys with a closure
+pub struct FnComparator<K, F> {
+    name: CString,
+    compare: F,
+    marker: PhantomData<fn() -> K>,
+}
+
+impl<K: Serializable, F: Fn(&K, &K) -> Ordering> FnComparator<K, F> {
+    /// Create a new FnComparator
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains a NUL byte.
+    pub fn new(name: &str, compare: F) -> FnComparator<K, F> {
+        FnComparator {
+            name: CString::new(name).expect("comparator name must not contain NUL bytes"),
+            compare: compare,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// RawFnComparator is a comparator comparing encoded keys with a closure
+pub struct RawFnComparator<F> {
+    name: CString,
+    compare: F,
+}
+
+impl<F: Fn(&[u8], &[u8]) -> Ordering> RawFnComparator<F> {
+    /// Create a new RawFnComparator
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains a NUL byte.
+    pub fn new(name: &str, compare: F) -> RawFnComparator<F> {
+        RawFnComparator {
+            name: CString::new(name).expect("comparator name must not contain NUL bytes"),
+            compare: compare,
         }
     }
 }
@@ -51,13 +109,130 @@ impl<K: Serializable + Ord> OrdComparator<K> {
 #[derive(Copy, Clone)]
 pub struct DefaultComparator;
 
-unsafe trait InternalComparator: Comparator
+/// A comparator working directly on the encoded keys.
+///
+/// Unlike `Comparator`, keys are not deserialized before comparing them,
+/// which avoids decoding and allocating on every comparison leveldb makes.
+/// The same rules apply: the name identifies the ordering on disk and
+/// the comparison must implement a total ordering.
+///
+/// leveldb's C API does not expose the `FindShortestSeparator` and
+/// `FindShortSuccessor` hooks of its comparators; they are always no-ops,
+/// so index blocks store full keys.
+///
+/// Like `Comparator`, it is called from several threads, so it must be
+/// `Send + Sync`.
+pub trait RawComparator: Send + Sync {
+    /// Return the name of the Comparator
+    fn name(&self) -> &CStr;
+    /// compare two encoded keys. This must implement a total ordering.
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering;
+}
+
+/// ReverseBytewiseComparator orders keys by their binary value, descending.
+#[derive(Copy, Clone)]
+pub struct ReverseBytewiseComparator;
+
+impl RawComparator for ReverseBytewiseComparator {
+    fn name(&self) -> &CStr {
+        c"leveldb.ReverseBytewiseComparator"
+    }
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+/// NumericSuffixComparator orders keys ending in ASCII digits naturally.
+///
+/// Keys are split into a prefix and a trailing run of digits. Prefixes are
+/// compared bytewise, the digits by their numeric value, so `file2` sorts
+/// before `file10`. Suffixes with the same value but different leading
+/// zeros fall back to bytewise order.
+#[derive(Copy, Clone)]
+pub struct NumericSuffixComparator;
+
+impl NumericSuffixComparator {
+    fn split(key: &[u8]) -> (&[u8], &[u8]) {
+        let digits = key.iter().rev().take_while(|b| b.is_ascii_digit()).count();
+        key.split_at(key.len() - digits)
+    }
+
+    fn strip_zeros(digits: &[u8]) -> &[u8] {
+        let zeros = digits.iter().take_while(|&&b| b == b'0').count();
+        &digits[zeros..]
+    }
+}
+
+impl RawComparator for NumericSuffixComparator {
+    fn name(&self) -> &CStr {
+        c"leveldb-rs.NumericSuffixComparator"
+    }
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        let (a_prefix, a_digits) = Self::split(a);
+        let (b_prefix, b_digits) = Self::split(b);
+        let (a_number, b_number) = (Self::strip_zeros(a_digits), Self::strip_zeros(b_digits));
+        a_prefix
+            .cmp(b_prefix)
+            .then(a_number.len().cmp(&b_number.len()))
+            .then(a_number.cmp(b_number))
+            .then(a.cmp(b))
+    }
+}
+
+/// Adapts a typed `Comparator` to compare encoded keys.
+pub(crate) struct TypedComparator<C>(pub(crate) C);
+
+impl<C: Comparator> RawComparator for TypedComparator<C> {
+    fn name(&self) -> &CStr {
+        self.0.name()
+    }
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        let a_key = try_from_u8::<C::K>(a);
+        let b_key = try_from_u8::<C::K>(b);
+        // Keys that can't be decoded sort after all valid keys, in
+        // binary order, so the ordering stays total.
+        match (a_key, b_key) {
+            (Ok(a_key), Ok(b_key)) => self.0.compare(&a_key, &b_key),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        }
+    }
+}
+
+/// The state passed to leveldb, pairing the comparator with the poison
+/// flag of the database using it.
+struct ComparatorState<C> {
+    comparator: C,
+    poisoned: Arc<AtomicBool>,
+}
+
+/// The callbacks handed to leveldb for a `RawComparator`.
+///
+/// Panics must not unwind into leveldb. If the comparator panics, the
+/// database is poisoned and a fallback result is handed to leveldb.
+///
+/// # Safety
+///
+/// The callbacks must only be passed to `leveldb_comparator_create` along
+/// with a `state` created by `Box::into_raw` from a
+/// `Box<ComparatorState<Self>>`, which `destructor` releases.
+unsafe trait InternalComparator: RawComparator
 where
     Self: Sized,
 {
     extern "C" fn name(state: *mut c_void) -> *const c_char {
-        let x = unsafe { &*(state as *mut Self) };
-        x.name()
+        let x = unsafe { &*(state as *mut ComparatorState<Self>) };
+        match panic::catch_unwind(AssertUnwindSafe(|| x.comparator.name().as_ptr())) {
+            Ok(name) => name,
+            Err(_) => {
+                x.poisoned.store(true, atomic::Ordering::SeqCst);
+                POISONED_NAME.as_ptr()
+            }
+        }
     }
 
     extern "C" fn compare(
@@ -70,10 +245,14 @@ where
         unsafe {
             let a_slice = slice::from_raw_parts::<u8>(a as *const u8, a_len as usize);
             let b_slice = slice::from_raw_parts::<u8>(b as *const u8, b_len as usize);
-            let x = &*(state as *mut Self);
-            let a_key = from_u8::<<Self as Comparator>::K>(a_slice);
-            let b_key = from_u8::<<Self as Comparator>::K>(b_slice);
-            match x.compare(&a_key, &b_key) {
+            let x = &*(state as *mut ComparatorState<Self>);
+            let compared =
+                panic::catch_unwind(AssertUnwindSafe(|| x.comparator.compare(a_slice, b_slice)));
+            let ordering = compared.unwrap_or_else(|_| {
+                x.poisoned.store(true, atomic::Ordering::SeqCst);
+                a_slice.cmp(b_slice)
+            });
+            match ordering {
                 Ordering::Less => -1,
                 Ordering::Equal => 0,
                 Ordering::Greater => 1,
@@ -82,31 +261,107 @@ where
     }
 
     extern "C" fn destructor(state: *mut c_void) {
-        let _x: Box<Self> = unsafe { Box::from_raw(state as *mut Self) };
+        let x: Box<ComparatorState<Self>> =
+            unsafe { Box::from_raw(state as *mut ComparatorState<Self>) };
         // let the Box fall out of scope and run the T's destructor
+        let _ = panic::catch_unwind(AssertUnwindSafe(move || drop(x)));
+    }
+}
+
+unsafe impl<C: RawComparator> InternalComparator for C {}
+
+const POISONED_NAME: &CStr = c"poisoned";
+
+type CompareFn = extern "C" fn(*mut c_void, *const c_char, size_t, *const c_char, size_t) -> i32;
+
+/// The order of keys in a database: bytewise, or that of its comparator.
+///
+/// Points into the comparator state owned by leveldb, so it must not
+/// outlive the `leveldb_comparator_t` it was created with.
+#[derive(Copy, Clone)]
+pub(crate) struct KeyOrder {
+    comparator: Option<(*mut c_void, CompareFn)>,
+}
+
+// SAFETY: the state points to a comparator implementing `RawComparator`,
+// which requires `Send + Sync`, and it is only read through `&`. leveldb
+// already calls it from its background threads, so comparing keys from an
+// iterator's thread adds no new kind of access.
+unsafe impl Send for KeyOrder {}
+unsafe impl Sync for KeyOrder {}
+
+impl KeyOrder {
+    /// leveldb's default order, comparing the encoded keys bytewise.
+    pub(crate) fn bytewise() -> KeyOrder {
+        KeyOrder { comparator: None }
+    }
+
+    /// Compare two encoded keys the way the database does.
+    pub(crate) fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        match self.comparator {
+            Some((state, compare)) => compare(
+                state,
+                a.as_ptr() as *const c_char,
+                a.len() as size_t,
+                b.as_ptr() as *const c_char,
+                b.len() as size_t,
+            )
+            .cmp(&0),
+            None => a.cmp(b),
+        }
     }
 }
 
-unsafe impl<C: Comparator> InternalComparator for C {}
+/// Create a leveldb comparator from `x`.
+///
+/// `poisoned` is set if the comparator panics.
+pub fn create_comparator<T: Comparator>(
+    x: T,
+    poisoned: Arc<AtomicBool>,
+) -> *mut leveldb_comparator_t {
+    create_raw_comparator(TypedComparator(x), poisoned)
+}
 
-#[allow(missing_docs)]
-pub fn create_comparator<T: Comparator>(x: Box<T>) -> *mut leveldb_comparator_t {
-    unsafe {
+/// Create a leveldb comparator from the raw comparator `x`.
+///
+/// `poisoned` is set if the comparator panics.
+pub fn create_raw_comparator<T: RawComparator>(
+    x: T,
+    poisoned: Arc<AtomicBool>,
+) -> *mut leveldb_comparator_t {
+    create_ordered_comparator(x, poisoned).0
+}
+
+/// Create a leveldb comparator from `x`, along with the order it
+/// imposes on keys.
+pub(crate) fn create_ordered_comparator<T: RawComparator>(
+    x: T,
+    poisoned: Arc<AtomicBool>,
+) -> (*mut leveldb_comparator_t, KeyOrder) {
+    let state = Box::into_raw(Box::new(ComparatorState {
+        comparator: x,
+        poisoned: poisoned,
+    })) as *mut c_void;
+    let compare: CompareFn = <T as InternalComparator>::compare;
+    let ptr = unsafe {
         leveldb_comparator_create(
-            Box::into_raw(x) as *mut c_void,
+            state,
             <T as InternalComparator>::destructor,
-            <T as InternalComparator>::compare,
+            compare,
             <T as InternalComparator>::name,
         )
-    }
+    };
+    let order = KeyOrder {
+        comparator: Some((state, compare)),
+    };
+    (ptr, order)
 }
 
 impl<K: Serializable + Ord> Comparator for OrdComparator<K> {
     type K = K;
 
-    fn name(&self) -> *const c_char {
-        let slice: &str = self.name.as_ref();
-        slice.as_ptr() as *const c_char
+    fn name(&self) -> &CStr {
+        &self.name
     }
 
     fn compare(&self, a: &K, b: &K) -> Ordering {
@@ -114,11 +369,33 @@ impl<K: Serializable + Ord> Comparator for OrdComparator<K> {
     }
 }
 
+impl<K: Serializable, F: Fn(&K, &K) -> Ordering + Send + Sync> Comparator for FnComparator<K, F> {
+    type K = K;
+
+    fn name(&self) -> &CStr {
+        &self.name
+    }
+
+    fn compare(&self, a: &K, b: &K) -> Ordering {
+        (self.compare)(a, b)
+    }
+}
+
+impl<F: Fn(&[u8], &[u8]) -> Ordering + Send + Sync> RawComparator for RawFnComparator<F> {
+    fn name(&self) -> &CStr {
+        &self.name
+    }
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        (self.compare)(a, b)
+    }
+}
+
 impl Comparator for DefaultComparator {
     type K = i32;
 
-    fn name(&self) -> *const c_char {
-        "default_comparator".as_ptr() as *const c_char
+    fn name(&self) -> &CStr {
+        c"default_comparator"
     }
 
     fn compare(&self, _a: &i32, _b: &i32) -> Ordering {
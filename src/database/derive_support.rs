@@ -0,0 +1,112 @@
+//! Runtime support for `#[derive(Serializable)]`.
+//!
+//! Not part of the public API, the functions here are only meant to be
+//! called from derived code.
+use super::error::Error;
+use super::serializable::malformed_key;
+
+/// Integers that can be stored in fewer bytes than their full width.
+pub trait FixedWidth: Sized {
+    #[allow(missing_docs)]
+    const SIGNED: bool;
+    #[allow(missing_docs)]
+    fn to_i128(self) -> i128;
+    #[allow(missing_docs)]
+    fn from_i128(value: i128) -> Option<Self>;
+}
+
+macro_rules! impl_fixed_width {
+    ($signed:expr; $($T:ty),*) => {$(
+        impl FixedWidth for $T {
+            const SIGNED: bool = $signed;
+
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+
+            fn from_i128(value: i128) -> Option<$T> {
+                <$T>::try_from(value).ok()
+            }
+        }
+    )*};
+}
+
+impl_fixed_width!(false; u8, u16, u32, u64, usize);
+impl_fixed_width!(true; i8, i16, i32, i64, isize);
+
+// Signed values are offset by the minimum of the width, which flips the sign
+// bit like the full-width encoding does.
+fn offset<T: FixedWidth>(width: usize) -> i128 {
+    if T::SIGNED {
+        1 << (width * 8 - 1)
+    } else {
+        0
+    }
+}
+
+#[allow(missing_docs)]
+pub fn encode_width<T: FixedWidth>(value: T, width: usize, out: &mut Vec<u8>) {
+    let value = value.to_i128();
+    let encoded = value + offset::<T>(width);
+    assert!(
+        encoded >= 0 && encoded < 1 << (width * 8),
+        "{} doesn't fit in {} bytes",
+        value,
+        width
+    );
+    out.extend_from_slice(&encoded.to_be_bytes()[16 - width..]);
+}
+
+#[allow(missing_docs)]
+pub fn decode_width<T: FixedWidth>(input: &mut &[u8], width: usize) -> Result<T, Error> {
+    if input.len() < width {
+        return Err(malformed_key("unexpected end of key".to_string()));
+    }
+    let (head, tail) = input.split_at(width);
+    *input = tail;
+    let encoded = head
+        .iter()
+        .fold(0i128, |acc, byte| (acc << 8) | i128::from(*byte));
+    T::from_i128(encoded - offset::<T>(width))
+        .ok_or_else(|| malformed_key(format!("{} bytes don't fit the field", width)))
+}
+
+/// Append the output of `encode`, complemented so that it sorts descending.
+pub fn encode_descending<F: FnOnce(&mut Vec<u8>)>(out: &mut Vec<u8>, encode: F) {
+    let start = out.len();
+    encode(out);
+    for byte in &mut out[start..] {
+        *byte = !*byte;
+    }
+}
+
+/// Decode a field written by `encode_descending`.
+pub fn decode_descending<T, F>(input: &mut &[u8], decode: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut &[u8]) -> Result<T, Error>,
+{
+    let complemented: Vec<u8> = input.iter().map(|byte| !*byte).collect();
+    let mut remaining = &complemented[..];
+    let value = decode(&mut remaining)?;
+    let consumed = complemented.len() - remaining.len();
+    *input = &input[consumed..];
+    Ok(value)
+}
+
+/// Decode a whole key with `decode`, rejecting trailing bytes.
+pub fn decode_all<T, F>(key: &[u8], decode: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut &[u8]) -> Result<T, Error>,
+{
+    let mut input = key;
+    let value = decode(&mut input)?;
+    if !input.is_empty() {
+        return Err(malformed_key(format!("{} trailing bytes", input.len())));
+    }
+    Ok(value)
+}
+
+#[allow(missing_docs)]
+pub fn invalid_variant(index: usize) -> Error {
+    malformed_key(format!("unknown variant {}", index))
+}
@@ -5,17 +5,99 @@ use libc::c_char;
 use libc::c_void;
 use std;
 
-/// A leveldb error, just containing the error string
-/// provided by leveldb.
+/// The category of a leveldb error.
+///
+/// leveldb reports errors as strings prefixed with the kind of the
+/// underlying status. The kind is recovered from that prefix, so callers
+/// can react to errors without matching on the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The requested entity was not found.
+    NotFound,
+    /// The data on disk is corrupted.
+    Corruption,
+    /// The operation is not supported.
+    NotSupported,
+    /// An argument, e.g. the database path or an option, is invalid.
+    InvalidArgument,
+    /// The database was created with a different comparator than the one
+    /// it is opened with. The names of both are available through
+    /// `Error::comparator_mismatch`.
+    ComparatorMismatch,
+    /// An I/O operation failed. Retrying may succeed.
+    IOError,
+    /// The database lock is held, usually because the database
+    /// is already open in this or another process.
+    Lock,
+    /// A user-provided callback panicked. A database whose comparator
+    /// panicked is poisoned and fails all further operations.
+    Poisoned,
+    /// Any error that doesn't fall into the categories above.
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify a status message as returned by leveldb.
+    pub fn from_message(message: &str) -> ErrorKind {
+        if message.starts_with("NotFound: ") {
+            ErrorKind::NotFound
+        } else if message.starts_with("Corruption: ") {
+            ErrorKind::Corruption
+        } else if message.starts_with("Not implemented: ") {
+            ErrorKind::NotSupported
+        } else if message.starts_with("Invalid argument: ") {
+            match comparator_names(message) {
+                Some(_) => ErrorKind::ComparatorMismatch,
+                None => ErrorKind::InvalidArgument,
+            }
+        } else if let Some(detail) = message.strip_prefix("IO error: ") {
+            // the env reports failures to acquire the LOCK file as
+            // "lock <path>: <reason>"
+            if detail.starts_with("lock ") {
+                ErrorKind::Lock
+            } else {
+                ErrorKind::IOError
+            }
+        } else {
+            ErrorKind::Other
+        }
+    }
+}
+
+// leveldb reports "Invalid argument: <expected> does not match existing
+// comparator : <actual>"
+fn comparator_names(message: &str) -> Option<(&str, &str)> {
+    message
+        .strip_prefix("Invalid argument: ")?
+        .split_once(" does not match existing comparator : ")
+}
+
+/// A leveldb error, containing the error string
+/// provided by leveldb and its kind.
 #[derive(Debug)]
 pub struct Error {
     pub message: String,
+    kind: ErrorKind,
 }
 
 impl Error {
     /// create a new Error, using the String provided
+    ///
+    /// The kind is derived from the message.
     pub fn new(message: String) -> Error {
-        Error { message: message }
+        Error {
+            kind: ErrorKind::from_message(&message),
+            message: message,
+        }
+    }
+
+    /// create a new Error of the given kind, using the String provided
+    pub fn with_kind(kind: ErrorKind, message: String) -> Error {
+        Error {
+            message: message,
+            kind: kind,
+        }
     }
 
     /// create an error from a c-string buffer.
@@ -24,14 +106,31 @@ impl Error {
     /// The pointer will be passed to `free`!
     pub unsafe fn new_from_char(message: *const c_char) -> Error {
         use std::ffi::CStr;
-        use std::str::from_utf8;
 
-        let err_string = from_utf8(CStr::from_ptr(message).to_bytes())
-            .unwrap()
-            .to_string();
+        let err_string = CStr::from_ptr(message).to_string_lossy().into_owned();
         leveldb_free(message as *mut c_void);
         Error::new(err_string)
     }
+
+    /// The kind of this error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The error string provided by leveldb
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// For errors of kind `ErrorKind::ComparatorMismatch`, the names of
+    /// the comparator the database was created with and of the one
+    /// passed when opening it, in that order.
+    pub fn comparator_mismatch(&self) -> Option<(&str, &str)> {
+        match self.kind {
+            ErrorKind::ComparatorMismatch => comparator_names(&self.message),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
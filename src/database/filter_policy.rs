@@ -0,0 +1,198 @@
+//! Structs and traits to work with leveldb filter policies.
+//!
+//! leveldb ships with a bloom filter policy, available through
+//! `FilterPolicy::new_bloom`. Custom policies can be implemented in Rust
+//! using the `CustomFilterPolicy` trait and passed to `FilterPolicy::new_custom`.
+use crate::binding::{
+    leveldb_filterpolicy_create, leveldb_filterpolicy_create_bloom, leveldb_filterpolicy_destroy,
+    leveldb_filterpolicy_t,
+};
+use libc::{c_char, c_int, c_uchar, c_void, size_t};
+use std::alloc::{self, Layout};
+use std::ffi::CStr;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+/// A filter policy implemented in Rust.
+///
+/// The policy works on the raw, serialized keys, which allows e.g. filtering
+/// on a prefix of the key only.
+///
+/// leveldb calls the policy from its background compaction thread and from
+/// all threads reading from the database, so it must be `Send + Sync`.
+pub trait CustomFilterPolicy: Send + Sync {
+    /// Return the name of the policy.
+    ///
+    /// If the filter encoding changes in an incompatible way, the name
+    /// must change as well, otherwise old filters would be passed to the
+    /// new policy.
+    fn name(&self) -> &CStr;
+    /// Build a filter summarizing `keys`.
+    ///
+    /// `keys` are ordered by the comparator and may contain duplicates.
+    fn create_filter(&self, keys: &[&[u8]]) -> Vec<u8>;
+    /// Return `true` if `key` may have been in the key list passed to
+    /// `create_filter` that produced `filter`.
+    ///
+    /// This must return `true` if the key was in the list, but may return
+    /// `true` or `false` if it was not.
+    fn key_may_match(&self, key: &[u8], filter: &[u8]) -> bool;
+}
+
+#[allow(missing_docs)]
+struct RawFilterPolicy {
+    ptr: *mut leveldb_filterpolicy_t,
+}
+
+impl Drop for RawFilterPolicy {
+    fn drop(&mut self) {
+        unsafe {
+            leveldb_filterpolicy_destroy(self.ptr);
+        }
+    }
+}
+
+/// Represents a leveldb filter policy
+///
+/// A filter policy lets leveldb skip reading data blocks for keys that are
+/// known to be absent, which greatly reduces disk reads for lookups of
+/// missing keys.
+pub struct FilterPolicy {
+    raw: RawFilterPolicy,
+}
+
+impl FilterPolicy {
+    /// Create a leveldb bloom filter policy using approximately
+    /// `bits_per_key` bits per key.
+    ///
+    /// A good value for `bits_per_key` is 10, which yields a filter
+    /// with ~1% false positive rate.
+    pub fn new_bloom(bits_per_key: i32) -> FilterPolicy {
+        let policy = unsafe { leveldb_filterpolicy_create_bloom(bits_per_key as c_int) };
+        FilterPolicy {
+            raw: RawFilterPolicy { ptr: policy },
+        }
+    }
+
+    /// Create a filter policy backed by a Rust implementation.
+    pub fn new_custom<P: CustomFilterPolicy>(policy: P) -> FilterPolicy {
+        FilterPolicy {
+            raw: RawFilterPolicy {
+                ptr: create_filter_policy(Box::new(policy)),
+            },
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn raw_ptr(&self) -> *mut leveldb_filterpolicy_t {
+        self.raw.ptr
+    }
+}
+
+// Panics must not unwind into leveldb. Filters are stored with a leading
+// marker byte, so a filter that failed to build can be told apart and
+// treated as matching every key. A panic while matching also matches, so
+// a failing policy only costs extra reads and never hides data.
+const FILTER_FAILED: u8 = 0;
+const FILTER_BUILT: u8 = 1;
+const POISONED_NAME: &CStr = c"poisoned";
+
+/// The callbacks handed to leveldb for a `CustomFilterPolicy`.
+///
+/// # Safety
+///
+/// The callbacks must only be passed to `leveldb_filterpolicy_create`
+/// along with a `state` created by `Box::into_raw` from a `Box<Self>`,
+/// which `destructor` releases.
+unsafe trait InternalFilterPolicy: CustomFilterPolicy
+where
+    Self: Sized,
+{
+    extern "C" fn name(state: *mut c_void) -> *const c_char {
+        let x = unsafe { &*(state as *mut Self) };
+        match panic::catch_unwind(AssertUnwindSafe(|| x.name().as_ptr())) {
+            Ok(name) => name,
+            Err(_) => POISONED_NAME.as_ptr(),
+        }
+    }
+
+    extern "C" fn create_filter(
+        state: *mut c_void,
+        key_array: *const *const c_char,
+        key_length_array: *const size_t,
+        num_keys: c_int,
+        filter_length: *mut size_t,
+    ) -> *mut c_char {
+        unsafe {
+            let x = &*(state as *mut Self);
+            let num_keys = num_keys as usize;
+            let key_ptrs = slice::from_raw_parts(key_array, num_keys);
+            let key_lens = slice::from_raw_parts(key_length_array, num_keys);
+            let keys: Vec<&[u8]> = key_ptrs
+                .iter()
+                .zip(key_lens)
+                .map(|(&k, &len)| slice::from_raw_parts::<u8>(k as *const u8, len))
+                .collect();
+            let filter = match panic::catch_unwind(AssertUnwindSafe(|| x.create_filter(&keys))) {
+                Ok(mut filter) => {
+                    filter.insert(0, FILTER_BUILT);
+                    filter
+                }
+                Err(_) => vec![FILTER_FAILED],
+            };
+
+            // leveldb releases the filter using `free`, so it has to be
+            // allocated using `malloc`.
+            let result = libc::malloc(filter.len()) as *mut c_char;
+            if result.is_null() {
+                alloc::handle_alloc_error(Layout::array::<u8>(filter.len()).unwrap());
+            }
+            ptr::copy_nonoverlapping(filter.as_ptr() as *const c_char, result, filter.len());
+            *filter_length = filter.len() as size_t;
+            result
+        }
+    }
+
+    extern "C" fn key_may_match(
+        state: *mut c_void,
+        key: *const c_char,
+        length: size_t,
+        filter: *const c_char,
+        filter_length: size_t,
+    ) -> c_uchar {
+        unsafe {
+            let x = &*(state as *mut Self);
+            let key_slice = slice::from_raw_parts::<u8>(key as *const u8, length);
+            let filter_slice = slice::from_raw_parts::<u8>(filter as *const u8, filter_length);
+            match filter_slice.split_first() {
+                Some((&FILTER_BUILT, filter_slice)) => panic::catch_unwind(AssertUnwindSafe(|| {
+                    x.key_may_match(key_slice, filter_slice)
+                }))
+                .unwrap_or(true) as c_uchar,
+                _ => 1,
+            }
+        }
+    }
+
+    extern "C" fn destructor(state: *mut c_void) {
+        let x: Box<Self> = unsafe { Box::from_raw(state as *mut Self) };
+        // let the Box fall out of scope and run the T's destructor
+        let _ = panic::catch_unwind(AssertUnwindSafe(move || drop(x)));
+    }
+}
+
+unsafe impl<P: CustomFilterPolicy> InternalFilterPolicy for P {}
+
+#[allow(missing_docs)]
+pub fn create_filter_policy<P: CustomFilterPolicy>(x: Box<P>) -> *mut leveldb_filterpolicy_t {
+    unsafe {
+        leveldb_filterpolicy_create(
+            Box::into_raw(x) as *mut c_void,
+            <P as InternalFilterPolicy>::destructor,
+            <P as InternalFilterPolicy>::create_filter,
+            <P as InternalFilterPolicy>::key_may_match,
+            <P as InternalFilterPolicy>::name,
+        )
+    }
+}
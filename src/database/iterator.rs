@@ -2,19 +2,286 @@
 //!
 //! Iteration is one of the most important parts of leveldb. This module provides
 //! Iterators to iterate over key, values and pairs of both.
+//!
+//! `range`, `range_keys` and `range_values` iterate over the keys within
+//! any `RangeBounds`, compared in the order of the database. Ranges keep
+//! their bounds when reversed.
+//!
+//! `cursor` returns a `RawCursor`, which hands out the encoded key and value
+//! of its current entry without decoding or copying them.
+//!
+//! `ArcIterator` holds an `Arc` of its database instead of borrowing it,
+//! so it can be stored next to the database or outlive the scope that
+//! opened it.
+//!
+//! `prefix_iter`, `prefix_keys` and `prefix_values` iterate over the keys
+//! whose encoding starts with a prefix. Use `strip_prefix` to get the rest
+//! of these keys as raw bytes.
+//!
+//! A standard `Iterator` ends both at the end of the keyspace and when
+//! leveldb hits an error (e.g. a corrupted block). Use `status`, `try_next`
+//! or the `fallible` adapter to tell the two apart.
+use super::comparator::KeyOrder;
+use super::error::Error;
 use super::options::{c_readoptions, ReadOptions};
-use super::serializable::{from_u8, Serializable};
-use super::Database;
+use super::serializable::{from_u8, try_from_u8, Serializable};
+use super::snapshots::OwnedSnapshot;
+use super::{poisoned_error, prefix_successor, Database};
 use crate::binding::{
-    leveldb_create_iterator, leveldb_iter_destroy, leveldb_iter_key, leveldb_iter_next,
-    leveldb_iter_prev, leveldb_iter_seek, leveldb_iter_seek_to_first, leveldb_iter_seek_to_last,
-    leveldb_iter_valid, leveldb_iter_value, leveldb_iterator_t, leveldb_readoptions_destroy,
+    leveldb_create_iterator, leveldb_iter_destroy, leveldb_iter_get_error, leveldb_iter_key,
+    leveldb_iter_next, leveldb_iter_prev, leveldb_iter_seek, leveldb_iter_seek_to_first,
+    leveldb_iter_seek_to_last, leveldb_iter_valid, leveldb_iter_value, leveldb_iterator_t,
+    leveldb_readoptions_destroy,
 };
 use libc::{c_char, size_t};
-use std::cmp::Ord;
+use std::cmp;
 use std::iter;
 use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
 use std::slice::from_raw_parts;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Return the key the raw iterator is positioned at.
+unsafe fn raw_iter_key<'b>(ptr: *mut leveldb_iterator_t) -> &'b [u8] {
+    let length: size_t = 0;
+    let value = leveldb_iter_key(ptr, &length) as *const u8;
+    from_raw_parts(value, length as usize)
+}
+
+/// Position `ptr` on the first key starting with `namespace`.
+unsafe fn seek_namespace_first(ptr: *mut leveldb_iterator_t, namespace: &[u8]) {
+    if namespace.is_empty() {
+        leveldb_iter_seek_to_first(ptr)
+    } else {
+        leveldb_iter_seek(ptr, namespace.as_ptr() as *const c_char, namespace.len());
+    }
+}
+
+/// Position `ptr` on the last key starting with `namespace`.
+unsafe fn seek_namespace_last(ptr: *mut leveldb_iterator_t, namespace: &[u8]) {
+    match prefix_successor(namespace) {
+        Some(end) => {
+            leveldb_iter_seek(ptr, end.as_ptr() as *const c_char, end.len());
+            if leveldb_iter_valid(ptr) == 0 {
+                leveldb_iter_seek_to_last(ptr);
+            } else {
+                leveldb_iter_prev(ptr);
+            }
+        }
+        None => leveldb_iter_seek_to_last(ptr),
+    }
+}
+
+/// Position `ptr` within the keys starting with `prefix`, if any, seeking
+/// to `target` next to them.
+unsafe fn seek_prefix(ptr: *mut leveldb_iterator_t, prefix: &[u8], target: &[u8]) {
+    leveldb_iter_seek(ptr, target.as_ptr() as *const c_char, target.len());
+    if at_prefix(ptr, prefix) {
+        return;
+    }
+    // depending on the comparator, the keys starting with `prefix` come
+    // right before or right after `target`
+    if leveldb_iter_valid(ptr) == 0 {
+        leveldb_iter_seek_to_last(ptr);
+    } else {
+        leveldb_iter_prev(ptr);
+    }
+    if at_prefix(ptr, prefix) {
+        return;
+    }
+    leveldb_iter_seek(ptr, target.as_ptr() as *const c_char, target.len());
+    if leveldb_iter_valid(ptr) != 0 {
+        leveldb_iter_next(ptr);
+    }
+}
+
+/// Whether `ptr` is positioned at a key starting with `prefix`.
+unsafe fn at_prefix(ptr: *mut leveldb_iterator_t, prefix: &[u8]) -> bool {
+    leveldb_iter_valid(ptr) != 0 && raw_iter_key(ptr).starts_with(prefix)
+}
+
+/// Position `ptr` on the first key starting with `prefix`.
+///
+/// The position doesn't depend on the comparator of the database, as long
+/// as it orders the keys starting with `prefix` next to each other.
+unsafe fn seek_prefix_first(ptr: *mut leveldb_iterator_t, prefix: &[u8]) {
+    if prefix.is_empty() {
+        return leveldb_iter_seek_to_first(ptr);
+    }
+    seek_prefix(ptr, prefix, prefix);
+    if at_prefix(ptr, prefix) {
+        // for the bytewise order, this steps back a single key
+        leveldb_iter_prev(ptr);
+        while at_prefix(ptr, prefix) {
+            leveldb_iter_prev(ptr);
+        }
+        if leveldb_iter_valid(ptr) == 0 {
+            leveldb_iter_seek_to_first(ptr);
+        } else {
+            leveldb_iter_next(ptr);
+        }
+    }
+}
+
+/// Position `ptr` on the last key starting with `prefix`.
+unsafe fn seek_prefix_last(ptr: *mut leveldb_iterator_t, prefix: &[u8]) {
+    if prefix.is_empty() {
+        return leveldb_iter_seek_to_last(ptr);
+    }
+    match prefix_successor(prefix) {
+        Some(end) => seek_prefix(ptr, prefix, &end),
+        None => seek_prefix(ptr, prefix, prefix),
+    }
+    if at_prefix(ptr, prefix) {
+        leveldb_iter_next(ptr);
+        while at_prefix(ptr, prefix) {
+            leveldb_iter_next(ptr);
+        }
+        if leveldb_iter_valid(ptr) == 0 {
+            leveldb_iter_seek_to_last(ptr);
+        } else {
+            leveldb_iter_prev(ptr);
+        }
+    }
+}
+
+/// `key` without the prefix of its namespace.
+fn strip_namespace<'b>(key: &'b [u8], namespace: &[u8]) -> &'b [u8] {
+    key.get(namespace.len()..).unwrap_or(&[])
+}
+
+/// The encoded bounds of a range iteration.
+struct KeyRange {
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    // the prefix of all keys of a prefix iteration, which replaces the
+    // bounds, so the iteration ends independently of the comparator
+    prefix: Option<Vec<u8>>,
+    // the length of the common prefix of all keys in the range
+    prefix_len: usize,
+}
+
+impl KeyRange {
+    fn new<K: Serializable, R: RangeBounds<K>>(range: R) -> KeyRange {
+        let encode = |bound: Bound<&K>| match bound {
+            Bound::Included(key) => Bound::Included(key.as_u8()),
+            Bound::Excluded(key) => Bound::Excluded(key.as_u8()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        KeyRange {
+            start: encode(range.start_bound()),
+            end: encode(range.end_bound()),
+            prefix: None,
+            prefix_len: 0,
+        }
+    }
+
+    /// The range of keys whose encoding starts with `prefix`.
+    fn prefix(prefix: &[u8]) -> KeyRange {
+        KeyRange {
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+            prefix: Some(prefix.to_vec()),
+            prefix_len: prefix.len(),
+        }
+    }
+
+    /// This range within the keys starting with `namespace`.
+    fn within(self, namespace: &[u8]) -> KeyRange {
+        if namespace.is_empty() {
+            return self;
+        }
+        let prefixed = |key: Vec<u8>| [namespace, &key].concat();
+        if let Some(prefix) = self.prefix {
+            return KeyRange {
+                prefix: Some(prefixed(prefix)),
+                ..self
+            };
+        }
+        let start = match self.start {
+            Bound::Included(key) => Bound::Included(prefixed(key)),
+            Bound::Excluded(key) => Bound::Excluded(prefixed(key)),
+            Bound::Unbounded => Bound::Included(namespace.to_vec()),
+        };
+        let end = match self.end {
+            Bound::Included(key) => Bound::Included(prefixed(key)),
+            Bound::Excluded(key) => Bound::Excluded(prefixed(key)),
+            Bound::Unbounded => match prefix_successor(namespace) {
+                Some(end) => Bound::Excluded(end),
+                None => Bound::Unbounded,
+            },
+        };
+        KeyRange {
+            start: start,
+            end: end,
+            prefix: None,
+            prefix_len: self.prefix_len,
+        }
+    }
+
+    /// Position `ptr` on the first key of the range.
+    unsafe fn seek_first(&self, ptr: *mut leveldb_iterator_t, order: &KeyOrder) {
+        if let Some(ref prefix) = self.prefix {
+            return seek_prefix_first(ptr, prefix);
+        }
+        match self.start {
+            Bound::Unbounded => leveldb_iter_seek_to_first(ptr),
+            Bound::Included(ref key) | Bound::Excluded(ref key) => {
+                leveldb_iter_seek(ptr, key.as_ptr() as *const c_char, key.len() as size_t);
+                if let Bound::Excluded(_) = self.start {
+                    if leveldb_iter_valid(ptr) != 0
+                        && order.compare(raw_iter_key(ptr), key) == cmp::Ordering::Equal
+                    {
+                        leveldb_iter_next(ptr);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Position `ptr` on the last key of the range.
+    unsafe fn seek_last(&self, ptr: *mut leveldb_iterator_t, order: &KeyOrder) {
+        if let Some(ref prefix) = self.prefix {
+            return seek_prefix_last(ptr, prefix);
+        }
+        match self.end {
+            Bound::Unbounded => leveldb_iter_seek_to_last(ptr),
+            Bound::Included(ref key) | Bound::Excluded(ref key) => {
+                leveldb_iter_seek(ptr, key.as_ptr() as *const c_char, key.len() as size_t);
+                if leveldb_iter_valid(ptr) == 0 {
+                    leveldb_iter_seek_to_last(ptr);
+                } else if self.after_end(raw_iter_key(ptr), order) {
+                    leveldb_iter_prev(ptr);
+                }
+            }
+        }
+    }
+
+    fn before_start(&self, key: &[u8], order: &KeyOrder) -> bool {
+        if let Some(ref prefix) = self.prefix {
+            return !key.starts_with(prefix);
+        }
+        match self.start {
+            Bound::Included(ref start) => order.compare(key, start) == cmp::Ordering::Less,
+            Bound::Excluded(ref start) => order.compare(key, start) != cmp::Ordering::Greater,
+            Bound::Unbounded => false,
+        }
+    }
+
+    fn after_end(&self, key: &[u8], order: &KeyOrder) -> bool {
+        if let Some(ref prefix) = self.prefix {
+            return !key.starts_with(prefix);
+        }
+        match self.end {
+            Bound::Included(ref end) => order.compare(key, end) == cmp::Ordering::Greater,
+            Bound::Excluded(ref end) => order.compare(key, end) != cmp::Ordering::Less,
+            Bound::Unbounded => false,
+        }
+    }
+}
 
 #[allow(missing_docs)]
 struct RawIterator {
@@ -28,6 +295,16 @@ impl Drop for RawIterator {
     }
 }
 
+// SAFETY: a leveldb iterator is not synchronised, so it must not be used
+// from two threads at once, but it isn't tied to the thread that created
+// it either. It only reads from the database (and snapshot) it was created
+// from, which are safe to use from any thread. `RawIterator` is owned by a
+// single iterator, and every call through it goes through `&self` or
+// `&mut self` of that owner, so moving it to another thread is sound.
+// It is deliberately not `Sync`: two threads sharing `&Iterator` could call
+// `seek` concurrently, which mutates the leveldb iterator.
+unsafe impl Send for RawIterator {}
+
 /// An iterator over the leveldb keyspace.
 ///
 /// Returns key and value as a tuple.
@@ -39,6 +316,11 @@ pub struct Iterator<'a, K: Serializable + 'a> {
     #[allow(dead_code)]
     database: PhantomData<&'a Database<K>>,
     iter: RawIterator,
+    poisoned: Arc<AtomicBool>,
+    order: KeyOrder,
+    range: Option<KeyRange>,
+    // the key prefix of the namespace iterated over, if any
+    namespace: &'a [u8],
     from: Option<&'a K>,
     to: Option<&'a K>,
 }
@@ -54,6 +336,11 @@ pub struct RevIterator<'a, K: Serializable + 'a> {
     #[allow(dead_code)]
     database: PhantomData<&'a Database<K>>,
     iter: RawIterator,
+    poisoned: Arc<AtomicBool>,
+    order: KeyOrder,
+    range: Option<KeyRange>,
+    // the key prefix of the namespace iterated over, if any
+    namespace: &'a [u8],
     from: Option<&'a K>,
     to: Option<&'a K>,
 }
@@ -86,33 +373,171 @@ pub struct RevValueIterator<'a, K: Serializable + 'a> {
     inner: RevIterator<'a, K>,
 }
 
+/// A cursor over the leveldb keyspace, borrowing keys and values from leveldb.
+///
+/// Unlike the iterators, the cursor doesn't decode keys or copy values.
+/// `key_bytes` and `value_bytes` borrow from the cursor and stay valid until
+/// it is moved again.
+///
+/// A new cursor is positioned at the first entry.
+pub struct RawCursor<'a, K: Serializable + 'a> {
+    // RawCursor accesses the Database through a leveldb_iter_t pointer
+    // but needs to hold the reference for lifetime tracking
+    #[allow(dead_code)]
+    database: PhantomData<&'a Database<K>>,
+    iter: RawIterator,
+    poisoned: Arc<AtomicBool>,
+    namespace: &'a [u8],
+}
+
+/// An iterator holding a reference-counted database.
+///
+/// Unlike `Iterator`, it doesn't borrow the database and can be kept for
+/// as long as needed. `I` is the iterator it wraps, e.g. a `KeyIterator`
+/// after calling `keys`.
+pub struct ArcIterator<K: Serializable + 'static, I = Iterator<'static, K>> {
+    // declared first, so the leveldb iterator is destroyed before the
+    // snapshot and database it reads from
+    inner: I,
+    #[allow(dead_code)]
+    snapshot: Option<OwnedSnapshot<K>>,
+    #[allow(dead_code)]
+    database: Arc<Database<K>>,
+}
+
+/// An adapter yielding the keys of a prefix iteration without the prefix.
+///
+/// The rest of the keys is returned as raw bytes, as it generally can't be
+/// decoded as `K`.
+pub struct StripPrefix<I> {
+    inner: I,
+    prefix_len: usize,
+}
+
+/// An adapter yielding the items of a `LevelDBIterator` as `Result`s.
+///
+/// If leveldb fails while iterating, the error is yielded as the final item.
+pub struct Fallible<'a, K: Serializable + 'a, I> {
+    inner: I,
+    done: bool,
+    marker: PhantomData<(&'a (), K)>,
+}
+
 /// A trait to allow access to the three main iteration styles of leveldb.
 pub trait Iterable<'a, K: Serializable + 'a> {
     /// Return an Iterator iterating over (Key,Value) pairs
-    fn iter(&'a self, options: ReadOptions<'a, K>) -> Iterator<K>;
+    fn iter(&'a self, options: ReadOptions<'a, K>) -> Iterator<'a, K>;
     /// Returns an Iterator iterating over Keys only.
-    fn keys_iter(&'a self, options: ReadOptions<'a, K>) -> KeyIterator<K>;
+    fn keys_iter(&'a self, options: ReadOptions<'a, K>) -> KeyIterator<'a, K>;
     /// Returns an Iterator iterating over Values only.
-    fn value_iter(&'a self, options: ReadOptions<'a, K>) -> ValueIterator<K>;
+    fn value_iter(&'a self, options: ReadOptions<'a, K>) -> ValueIterator<'a, K>;
+
+    /// Return an Iterator iterating over the (Key,Value) pairs within `range`.
+    ///
+    /// The bounds are compared in the order of the database. Reversing the
+    /// iterator browses the same range backwards.
+    fn range<R: RangeBounds<K>>(&'a self, options: ReadOptions<'a, K>, range: R) -> Iterator<'a, K>
+    where
+        Self: Sized;
+    /// Returns an Iterator iterating over the Keys within `range` only.
+    fn range_keys<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> KeyIterator<'a, K>
+    where
+        Self: Sized;
+    /// Returns an Iterator iterating over the Values within `range` only.
+    fn range_values<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> ValueIterator<'a, K>
+    where
+        Self: Sized;
+
+    /// Returns a cursor over the encoded keys and values.
+    fn cursor(&'a self, options: ReadOptions<'a, K>) -> RawCursor<'a, K>;
+
+    /// Return an Iterator iterating over the (Key,Value) pairs whose encoded
+    /// key starts with `prefix`.
+    ///
+    /// Iteration ends at the first key not starting with `prefix`. With a
+    /// custom comparator, this covers all keys starting with `prefix` as
+    /// long as the comparator orders them next to each other.
+    fn prefix_iter(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> Iterator<'a, K>;
+    /// Returns an Iterator iterating over the Keys starting with `prefix` only.
+    fn prefix_keys(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> KeyIterator<'a, K>;
+    /// Returns an Iterator iterating over the Values of keys starting with
+    /// `prefix` only.
+    fn prefix_values(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> ValueIterator<'a, K>;
 }
 
-impl<'a, K: Serializable + Ord + 'a> Iterable<'a, K> for Database<K> {
-    fn iter(&'a self, options: ReadOptions<'a, K>) -> Iterator<K> {
+impl<'a, K: Serializable + 'a> Iterable<'a, K> for Database<K> {
+    fn iter(&'a self, options: ReadOptions<'a, K>) -> Iterator<'a, K> {
         Iterator::new(self, options)
     }
 
-    fn keys_iter(&'a self, options: ReadOptions<'a, K>) -> KeyIterator<K> {
+    fn keys_iter(&'a self, options: ReadOptions<'a, K>) -> KeyIterator<'a, K> {
         KeyIterator::new(self, options)
     }
 
-    fn value_iter(&'a self, options: ReadOptions<'a, K>) -> ValueIterator<K> {
+    fn value_iter(&'a self, options: ReadOptions<'a, K>) -> ValueIterator<'a, K> {
         ValueIterator::new(self, options)
     }
+
+    fn range<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> Iterator<'a, K> {
+        Iterator::new_range(self, options, KeyRange::new(range))
+    }
+
+    fn range_keys<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> KeyIterator<'a, K> {
+        KeyIterator {
+            inner: Iterator::new_range(self, options, KeyRange::new(range)),
+        }
+    }
+
+    fn range_values<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> ValueIterator<'a, K> {
+        ValueIterator {
+            inner: Iterator::new_range(self, options, KeyRange::new(range)),
+        }
+    }
+
+    fn cursor(&'a self, options: ReadOptions<'a, K>) -> RawCursor<'a, K> {
+        RawCursor::new(self, options)
+    }
+
+    fn prefix_iter(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> Iterator<'a, K> {
+        Iterator::new_range(self, options, KeyRange::prefix(prefix))
+    }
+
+    fn prefix_keys(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> KeyIterator<'a, K> {
+        KeyIterator {
+            inner: Iterator::new_range(self, options, KeyRange::prefix(prefix)),
+        }
+    }
+
+    fn prefix_values(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> ValueIterator<'a, K> {
+        ValueIterator {
+            inner: Iterator::new_range(self, options, KeyRange::prefix(prefix)),
+        }
+    }
 }
 
 #[allow(missing_docs)]
 #[allow(unused_attributes)]
-pub trait LevelDBIterator<'a, K: Serializable + Ord> {
+pub trait LevelDBIterator<'a, K: Serializable> {
     type RevIter: LevelDBIterator<'a, K>;
 
     #[inline]
@@ -130,6 +555,23 @@ pub trait LevelDBIterator<'a, K: Serializable + Ord> {
     #[inline]
     fn stopped(&self) -> bool;
 
+    #[doc(hidden)]
+    fn poisoned(&self) -> bool;
+
+    /// Compare two encoded keys in the order of the database.
+    #[doc(hidden)]
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> cmp::Ordering;
+
+    /// The prefix of all keys of the namespace iterated over, if any.
+    #[doc(hidden)]
+    fn key_prefix(&self) -> &[u8];
+
+    /// Encode `key` as stored in leveldb.
+    #[doc(hidden)]
+    fn encode_key(&self, key: &K) -> Vec<u8> {
+        [self.key_prefix(), &key.as_u8()].concat()
+    }
+
     fn reverse(self) -> Self::RevIter;
 
     fn from(self, key: &'a K) -> Self;
@@ -142,34 +584,99 @@ pub trait LevelDBIterator<'a, K: Serializable + Ord> {
         unsafe { leveldb_iter_valid(self.raw_iterator()) != 0 }
     }
 
+    /// Return the error leveldb encountered while iterating, if any.
+    ///
+    /// An iterator that hit an error is no longer valid, so iteration ends
+    /// as if the end of the keyspace was reached. Check the status after
+    /// iteration to distinguish both cases.
+    fn status(&self) -> Result<(), Error> {
+        if self.poisoned() {
+            return Err(poisoned_error());
+        }
+        unsafe {
+            let mut error = ptr::null_mut();
+            leveldb_iter_get_error(self.raw_iterator(), &mut error);
+            if error.is_null() {
+                Ok(())
+            } else {
+                Err(Error::new_from_char(error))
+            }
+        }
+    }
+
+    /// Advance the iterator, returning an error instead of `None`
+    /// if leveldb failed to read the next entry, and instead of panicking
+    /// if the key can't be decoded.
+    fn try_next(&mut self) -> Result<Option<<Self as iter::Iterator>::Item>, Error>
+    where
+        Self: iter::Iterator,
+    {
+        if self.poisoned() {
+            return Err(poisoned_error());
+        }
+        if self.advance() && !self.stopped() {
+            self.try_item().map(Some)
+        } else {
+            self.status().map(|_| None)
+        }
+    }
+
+    #[doc(hidden)]
+    fn try_item(&self) -> Result<<Self as iter::Iterator>::Item, Error>
+    where
+        Self: iter::Iterator;
+
+    /// Turn this into an iterator over `Result`s, which yields the
+    /// error leveldb encountered as the last item.
+    fn fallible(self) -> Fallible<'a, K, Self>
+    where
+        Self: iter::Iterator + Sized,
+    {
+        Fallible {
+            inner: self,
+            done: false,
+            marker: PhantomData,
+        }
+    }
+
     #[doc(hidden)]
     unsafe fn advance_raw(&mut self);
 
+    /// Position the iterator on the first entry to yield.
+    #[doc(hidden)]
+    fn seek_start(&mut self);
+
+    /// Whether the iterator moved past the last entry to yield.
+    #[doc(hidden)]
+    fn past_end(&self) -> bool;
+
     fn advance(&mut self) -> bool {
         unsafe {
             if self.started() && !self.stopped() {
                 self.advance_raw();
             } else {
-                if let Some(begin) = self.from_key() {
-                    self.seek(begin)
-                }
+                self.seek_start();
                 self.start();
             }
-            if let Some(end) = self.to_key() {
-                if !self.valid() || end <= &self.key() {
-                    self.stop();
-                }
+            if self.past_end() {
+                self.stop();
             }
         }
         self.valid()
     }
 
+    #[doc(hidden)]
+    fn raw_key(&self) -> &[u8] {
+        unsafe { raw_iter_key(self.raw_iterator()) }
+    }
+
     fn key(&self) -> K {
-        unsafe {
-            let length: size_t = 0;
-            let value = leveldb_iter_key(self.raw_iterator(), &length) as *const u8;
-            from_u8(from_raw_parts(value, length as usize))
-        }
+        from_u8(strip_namespace(self.raw_key(), self.key_prefix()))
+    }
+
+    /// Decode the current key, returning an error if it is malformed.
+    fn try_key(&self) -> Result<K, Error> {
+        try_from_u8(strip_namespace(self.raw_key(), self.key_prefix()))
     }
 
     fn value(&self) -> Vec<u8> {
@@ -184,8 +691,13 @@ pub trait LevelDBIterator<'a, K: Serializable + Ord> {
         (self.key(), self.value())
     }
 
+    /// Return the current entry, returning an error if the key is malformed.
+    fn try_entry(&self) -> Result<(K, Vec<u8>), Error> {
+        Ok((self.try_key()?, self.value()))
+    }
+
     fn seek_to_first(&self) {
-        unsafe { leveldb_iter_seek_to_first(self.raw_iterator()) }
+        unsafe { seek_namespace_first(self.raw_iterator(), self.key_prefix()) }
     }
 
     fn seek_to_last(&self) {
@@ -193,14 +705,14 @@ pub trait LevelDBIterator<'a, K: Serializable + Ord> {
             self.seek(k);
         } else {
             unsafe {
-                leveldb_iter_seek_to_last(self.raw_iterator());
+                seek_namespace_last(self.raw_iterator(), self.key_prefix());
             }
         }
     }
 
     fn seek(&self, key: &K) {
         unsafe {
-            let key = &key.as_u8();
+            let key = &self.encode_key(key);
 
             leveldb_iter_seek(
                 self.raw_iterator(),
@@ -211,17 +723,39 @@ pub trait LevelDBIterator<'a, K: Serializable + Ord> {
     }
 }
 
-impl<'a, K: Serializable + Ord> Iterator<'a, K> {
+impl<'a, K: Serializable> Iterator<'a, K> {
+    fn prefix_len(&self) -> usize {
+        self.namespace.len() + self.range.as_ref().map_or(0, |range| range.prefix_len)
+    }
+
+    fn new_range(
+        database: &'a Database<K>,
+        options: ReadOptions<'a, K>,
+        range: KeyRange,
+    ) -> Iterator<'a, K> {
+        let mut iter = Iterator::new(database, options);
+        iter.set_range(range);
+        iter
+    }
+
+    fn set_range(&mut self, range: KeyRange) {
+        self.range = Some(range.within(self.namespace));
+    }
+
     fn new(database: &'a Database<K>, options: ReadOptions<'a, K>) -> Iterator<'a, K> {
         unsafe {
             let c_readoptions = c_readoptions(&options);
             let ptr = leveldb_create_iterator(database.database.ptr, c_readoptions);
             leveldb_readoptions_destroy(c_readoptions);
-            leveldb_iter_seek_to_first(ptr);
+            seek_namespace_first(ptr, &database.prefix);
             Iterator {
                 started: false,
                 stopped: false,
                 iter: RawIterator { ptr: ptr },
+                poisoned: database.poisoned.clone(),
+                order: database.order,
+                range: None,
+                namespace: &database.prefix,
                 database: PhantomData,
                 from: None,
                 to: None,
@@ -231,12 +765,15 @@ impl<'a, K: Serializable + Ord> Iterator<'a, K> {
 
     /// return the last element of the iterator
     pub fn last(self) -> Option<(K, Vec<u8>)> {
+        if self.range.is_some() || !self.namespace.is_empty() {
+            return self.reverse().next();
+        }
         self.seek_to_last();
         Some((self.key(), self.value()))
     }
 }
 
-impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for Iterator<'a, K> {
+impl<'a, K: Serializable> LevelDBIterator<'a, K> for Iterator<'a, K> {
     type RevIter = RevIterator<'a, K>;
 
     #[inline]
@@ -259,6 +796,18 @@ impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for Iterator<'a, K> {
         self.stopped
     }
 
+    fn poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::SeqCst)
+    }
+
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> cmp::Ordering {
+        self.order.compare(a, b)
+    }
+
+    fn key_prefix(&self) -> &[u8] {
+        self.namespace
+    }
+
     #[inline]
     fn stop(&mut self) {
         self.stopped = true
@@ -269,11 +818,39 @@ impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for Iterator<'a, K> {
         leveldb_iter_next(self.raw_iterator());
     }
 
+    fn seek_start(&mut self) {
+        match self.range {
+            Some(ref range) => unsafe { range.seek_first(self.iter.ptr, &self.order) },
+            None => {
+                if let Some(begin) = self.from {
+                    self.seek(begin)
+                }
+            }
+        }
+    }
+
+    fn past_end(&self) -> bool {
+        match self.range {
+            Some(ref range) => self.valid() && range.after_end(self.raw_key(), &self.order),
+            None => {
+                self.to.is_some_and(|end| {
+                    !self.valid()
+                        || self.compare_keys(&self.encode_key(end), self.raw_key())
+                            != cmp::Ordering::Greater
+                }) || (self.valid() && !self.raw_key().starts_with(self.namespace))
+            }
+        }
+    }
+
+    fn try_item(&self) -> Result<<Self as iter::Iterator>::Item, Error> {
+        self.try_entry()
+    }
+
     #[inline]
     fn reverse(self) -> Self::RevIter {
         if !self.started {
             unsafe {
-                leveldb_iter_seek_to_last(self.iter.ptr);
+                seek_namespace_last(self.iter.ptr, self.namespace);
             }
         }
         RevIterator {
@@ -281,6 +858,10 @@ impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for Iterator<'a, K> {
             stopped: self.stopped,
             database: self.database,
             iter: self.iter,
+            poisoned: self.poisoned,
+            order: self.order,
+            range: self.range,
+            namespace: self.namespace,
             from: self.from,
             to: self.to,
         }
@@ -305,7 +886,7 @@ impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for Iterator<'a, K> {
     }
 }
 
-impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for RevIterator<'a, K> {
+impl<'a, K: Serializable> LevelDBIterator<'a, K> for RevIterator<'a, K> {
     type RevIter = Iterator<'a, K>;
 
     #[inline]
@@ -333,16 +914,56 @@ impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for RevIterator<'a, K> {
         self.stopped
     }
 
+    fn poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::SeqCst)
+    }
+
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> cmp::Ordering {
+        self.order.compare(a, b)
+    }
+
+    fn key_prefix(&self) -> &[u8] {
+        self.namespace
+    }
+
     #[inline]
     unsafe fn advance_raw(&mut self) {
         leveldb_iter_prev(self.raw_iterator());
     }
 
+    fn seek_start(&mut self) {
+        match self.range {
+            Some(ref range) => unsafe { range.seek_last(self.iter.ptr, &self.order) },
+            None => {
+                if let Some(begin) = self.from {
+                    self.seek(begin)
+                }
+            }
+        }
+    }
+
+    fn past_end(&self) -> bool {
+        match self.range {
+            Some(ref range) => self.valid() && range.before_start(self.raw_key(), &self.order),
+            None => {
+                self.to.is_some_and(|end| {
+                    !self.valid()
+                        || self.compare_keys(&self.encode_key(end), self.raw_key())
+                            != cmp::Ordering::Greater
+                }) || (self.valid() && !self.raw_key().starts_with(self.namespace))
+            }
+        }
+    }
+
+    fn try_item(&self) -> Result<<Self as iter::Iterator>::Item, Error> {
+        self.try_entry()
+    }
+
     #[inline]
     fn reverse(self) -> Self::RevIter {
         if !self.started {
             unsafe {
-                leveldb_iter_seek_to_first(self.iter.ptr);
+                seek_namespace_first(self.iter.ptr, self.namespace);
             }
         }
         Iterator {
@@ -350,6 +971,10 @@ impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for RevIterator<'a, K> {
             stopped: self.stopped,
             database: self.database,
             iter: self.iter,
+            poisoned: self.poisoned,
+            order: self.order,
+            range: self.range,
+            namespace: self.namespace,
             from: self.from,
             to: self.to,
         }
@@ -374,7 +999,23 @@ impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for RevIterator<'a, K> {
     }
 }
 
-impl<'a, K: Serializable + Ord> KeyIterator<'a, K> {
+impl<'a, K: Serializable> RevIterator<'a, K> {
+    fn prefix_len(&self) -> usize {
+        self.namespace.len() + self.range.as_ref().map_or(0, |range| range.prefix_len)
+    }
+}
+
+impl<'a, K: Serializable> RevKeyIterator<'a, K> {
+    fn prefix_len(&self) -> usize {
+        self.inner.prefix_len()
+    }
+}
+
+impl<'a, K: Serializable> KeyIterator<'a, K> {
+    fn prefix_len(&self) -> usize {
+        self.inner.prefix_len()
+    }
+
     fn new(database: &'a Database<K>, options: ReadOptions<'a, K>) -> KeyIterator<'a, K> {
         KeyIterator {
             inner: Iterator::new(database, options),
@@ -383,12 +1024,15 @@ impl<'a, K: Serializable + Ord> KeyIterator<'a, K> {
 
     /// return the last element of the iterator
     pub fn last(self) -> Option<K> {
+        if self.inner.range.is_some() || !self.inner.namespace.is_empty() {
+            return self.reverse().next();
+        }
         self.seek_to_last();
         Some(self.key())
     }
 }
 
-impl<'a, K: Serializable + Ord> ValueIterator<'a, K> {
+impl<'a, K: Serializable> ValueIterator<'a, K> {
     fn new(database: &'a Database<K>, options: ReadOptions<'a, K>) -> ValueIterator<'a, K> {
         ValueIterator {
             inner: Iterator::new(database, options),
@@ -397,14 +1041,17 @@ impl<'a, K: Serializable + Ord> ValueIterator<'a, K> {
 
     /// return the last element of the iterator
     pub fn last(self) -> Option<Vec<u8>> {
+        if self.inner.range.is_some() || !self.inner.namespace.is_empty() {
+            return self.inner.reverse().next().map(|(_, value)| value);
+        }
         self.seek_to_last();
         Some(self.value())
     }
 }
 
 macro_rules! impl_leveldb_iterator {
-    ($T:ty, $RevT:ty) => {
-        impl<'a, K: Serializable + Ord> LevelDBIterator<'a, K> for $T {
+    ($T:ty, $RevT:ty, |$self:ident| $try_item:expr) => {
+        impl<'a, K: Serializable> LevelDBIterator<'a, K> for $T {
             type RevIter = $RevT;
 
             #[inline]
@@ -432,11 +1079,35 @@ macro_rules! impl_leveldb_iterator {
                 self.inner.stopped
             }
 
+            fn poisoned(&self) -> bool {
+                self.inner.poisoned()
+            }
+
+            fn compare_keys(&self, a: &[u8], b: &[u8]) -> cmp::Ordering {
+                self.inner.compare_keys(a, b)
+            }
+
+            fn key_prefix(&self) -> &[u8] {
+                self.inner.key_prefix()
+            }
+
             #[inline]
             unsafe fn advance_raw(&mut self) {
                 self.inner.advance_raw();
             }
 
+            fn seek_start(&mut self) {
+                self.inner.seek_start()
+            }
+
+            fn past_end(&self) -> bool {
+                self.inner.past_end()
+            }
+
+            fn try_item(&$self) -> Result<<Self as iter::Iterator>::Item, Error> {
+                $try_item
+            }
+
             #[inline]
             fn reverse(self) -> Self::RevIter {
                 Self::RevIter {
@@ -465,14 +1136,20 @@ macro_rules! impl_leveldb_iterator {
     };
 }
 
-impl_leveldb_iterator!(KeyIterator<'a, K>, RevKeyIterator<'a, K>);
-impl_leveldb_iterator!(RevKeyIterator<'a, K>, KeyIterator<'a, K>);
-impl_leveldb_iterator!(ValueIterator<'a, K>, RevValueIterator<'a, K>);
-impl_leveldb_iterator!(RevValueIterator<'a, K>, ValueIterator<'a, K>);
+impl_leveldb_iterator!(KeyIterator<'a, K>, RevKeyIterator<'a, K>, |self| self
+    .try_key());
+impl_leveldb_iterator!(RevKeyIterator<'a, K>, KeyIterator<'a, K>, |self| self
+    .try_key());
+impl_leveldb_iterator!(ValueIterator<'a, K>, RevValueIterator<'a, K>, |self| Ok(
+    self.value()
+));
+impl_leveldb_iterator!(RevValueIterator<'a, K>, ValueIterator<'a, K>, |self| Ok(
+    self.value()
+));
 
 macro_rules! impl_iterator {
     ($T:ty, $Item:ty, $ItemMethod:ident) => {
-        impl<'a, K: Serializable + Ord> iter::Iterator for $T {
+        impl<'a, K: Serializable> iter::Iterator for $T {
             type Item = $Item;
             // The "next" method would iterate once, and stop at the end (to_key).
             // Use "advance" method if starting from begin (from_key) again is preferred
@@ -492,4 +1169,313 @@ impl_iterator!(RevIterator<'a, K>, (K, Vec<u8>), entry);
 impl_iterator!(KeyIterator<'a, K>, K, key);
 impl_iterator!(RevKeyIterator<'a, K>, K, key);
 impl_iterator!(ValueIterator<'a, K>, Vec<u8>, value);
-impl_iterator!(RevValueIterator<'a, K>, K, key);
+impl_iterator!(RevValueIterator<'a, K>, Vec<u8>, value);
+
+impl<'a, K: Serializable> RawCursor<'a, K> {
+    fn new(database: &'a Database<K>, options: ReadOptions<'a, K>) -> RawCursor<'a, K> {
+        unsafe {
+            let c_readoptions = c_readoptions(&options);
+            let ptr = leveldb_create_iterator(database.database.ptr, c_readoptions);
+            leveldb_readoptions_destroy(c_readoptions);
+            seek_namespace_first(ptr, &database.prefix);
+            RawCursor {
+                database: PhantomData,
+                iter: RawIterator { ptr: ptr },
+                poisoned: database.poisoned.clone(),
+                namespace: &database.prefix,
+            }
+        }
+    }
+
+    /// Whether the cursor is positioned at an entry.
+    pub fn valid(&self) -> bool {
+        unsafe {
+            leveldb_iter_valid(self.iter.ptr) != 0
+                && raw_iter_key(self.iter.ptr).starts_with(self.namespace)
+        }
+    }
+
+    /// Position the cursor at the first entry.
+    pub fn seek_to_first(&mut self) {
+        unsafe { seek_namespace_first(self.iter.ptr, self.namespace) }
+    }
+
+    /// Position the cursor at the last entry.
+    pub fn seek_to_last(&mut self) {
+        unsafe { seek_namespace_last(self.iter.ptr, self.namespace) }
+    }
+
+    /// Position the cursor at the first entry with a key at or after the
+    /// encoded `key`.
+    pub fn seek(&mut self, key: &[u8]) {
+        let key = [self.namespace, key].concat();
+        unsafe { leveldb_iter_seek(self.iter.ptr, key.as_ptr() as *const c_char, key.len()) }
+    }
+
+    /// Move to the next entry, returning whether the cursor is still valid.
+    ///
+    /// Does nothing if the cursor is not valid.
+    pub fn advance(&mut self) -> bool {
+        if self.valid() {
+            unsafe { leveldb_iter_next(self.iter.ptr) }
+        }
+        self.valid()
+    }
+
+    /// Move to the previous entry, returning whether the cursor is still valid.
+    ///
+    /// Does nothing if the cursor is not valid.
+    pub fn retreat(&mut self) -> bool {
+        if self.valid() {
+            unsafe { leveldb_iter_prev(self.iter.ptr) }
+        }
+        self.valid()
+    }
+
+    /// The encoded key of the current entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cursor is not valid.
+    pub fn key_bytes(&self) -> &[u8] {
+        assert!(self.valid(), "cursor is not positioned at an entry");
+        unsafe { strip_namespace(raw_iter_key(self.iter.ptr), self.namespace) }
+    }
+
+    /// The value of the current entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cursor is not valid.
+    pub fn value_bytes(&self) -> &[u8] {
+        assert!(self.valid(), "cursor is not positioned at an entry");
+        unsafe {
+            let length: size_t = 0;
+            let value = leveldb_iter_value(self.iter.ptr, &length) as *const u8;
+            from_raw_parts(value, length as usize)
+        }
+    }
+
+    /// Decode the key of the current entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cursor is not valid.
+    pub fn key(&self) -> Result<K, Error> {
+        try_from_u8(self.key_bytes())
+    }
+
+    /// Return the error leveldb encountered while moving the cursor, if any.
+    pub fn status(&self) -> Result<(), Error> {
+        if self.poisoned.load(Ordering::SeqCst) {
+            return Err(poisoned_error());
+        }
+        unsafe {
+            let mut error = ptr::null_mut();
+            leveldb_iter_get_error(self.iter.ptr, &mut error);
+            if error.is_null() {
+                Ok(())
+            } else {
+                Err(Error::new_from_char(error))
+            }
+        }
+    }
+}
+
+impl<K: Serializable + 'static> ArcIterator<K> {
+    /// Return an ArcIterator iterating over (Key,Value) pairs
+    pub fn new(database: Arc<Database<K>>, options: ReadOptions<'static, K>) -> ArcIterator<K> {
+        ArcIterator::build(database, None, options, None)
+    }
+
+    /// Return an ArcIterator iterating over the (Key,Value) pairs within `range`.
+    pub fn range<R: RangeBounds<K>>(
+        database: Arc<Database<K>>,
+        options: ReadOptions<'static, K>,
+        range: R,
+    ) -> ArcIterator<K> {
+        ArcIterator::build(database, None, options, Some(KeyRange::new(range)))
+    }
+
+    /// Return an ArcIterator iterating over the (Key,Value) pairs whose
+    /// encoded key starts with `prefix`.
+    pub fn prefix(
+        database: Arc<Database<K>>,
+        options: ReadOptions<'static, K>,
+        prefix: &[u8],
+    ) -> ArcIterator<K> {
+        ArcIterator::build(database, None, options, Some(KeyRange::prefix(prefix)))
+    }
+
+    /// Return an ArcIterator iterating over the (Key,Value) pairs of `snapshot`.
+    pub fn snapshot(
+        snapshot: OwnedSnapshot<K>,
+        options: ReadOptions<'static, K>,
+    ) -> ArcIterator<K> {
+        ArcIterator::build(snapshot.database().clone(), Some(snapshot), options, None)
+    }
+
+    /// Return an ArcIterator iterating over the (Key,Value) pairs of
+    /// `snapshot` within `range`.
+    pub fn snapshot_range<R: RangeBounds<K>>(
+        snapshot: OwnedSnapshot<K>,
+        options: ReadOptions<'static, K>,
+        range: R,
+    ) -> ArcIterator<K> {
+        let range = KeyRange::new(range);
+        ArcIterator::build(
+            snapshot.database().clone(),
+            Some(snapshot),
+            options,
+            Some(range),
+        )
+    }
+
+    fn build(
+        database: Arc<Database<K>>,
+        snapshot: Option<OwnedSnapshot<K>>,
+        mut options: ReadOptions<'static, K>,
+        range: Option<KeyRange>,
+    ) -> ArcIterator<K> {
+        // The database and snapshot live on the heap and are kept alive by
+        // the ArcIterator until after `inner` is dropped, so extending the
+        // borrows to 'static can't let them dangle.
+        let db = unsafe { &*Arc::as_ptr(&database) };
+        if let Some(ref snapshot) = snapshot {
+            let snapshot = snapshot.as_snapshot() as *const _ as *const _;
+            options.snapshot = Some(unsafe { &*snapshot });
+        }
+        let mut inner = Iterator::new(db, options);
+        if let Some(range) = range {
+            inner.set_range(range);
+        }
+        ArcIterator {
+            inner: inner,
+            snapshot: snapshot,
+            database: database,
+        }
+    }
+
+    /// Iterate over the keys only.
+    pub fn keys(self) -> ArcIterator<K, KeyIterator<'static, K>> {
+        self.map_inner(|inner| KeyIterator { inner: inner })
+    }
+
+    /// Iterate over the values only.
+    pub fn values(self) -> ArcIterator<K, ValueIterator<'static, K>> {
+        self.map_inner(|inner| ValueIterator { inner: inner })
+    }
+}
+
+impl<K: Serializable + 'static, I> ArcIterator<K, I> {
+    fn map_inner<J, F: FnOnce(I) -> J>(self, f: F) -> ArcIterator<K, J> {
+        ArcIterator {
+            inner: f(self.inner),
+            snapshot: self.snapshot,
+            database: self.database,
+        }
+    }
+
+    /// The database this iterator reads from.
+    pub fn database(&self) -> &Arc<Database<K>> {
+        &self.database
+    }
+}
+
+impl<K: Serializable + 'static, I: LevelDBIterator<'static, K>> ArcIterator<K, I> {
+    /// Browse the keys backwards.
+    pub fn reverse(self) -> ArcIterator<K, I::RevIter> {
+        self.map_inner(|inner| inner.reverse())
+    }
+
+    /// Advance the iterator, returning an error instead of `None`
+    /// if leveldb failed to read the next entry.
+    pub fn try_next(&mut self) -> Result<Option<<I as iter::Iterator>::Item>, Error>
+    where
+        I: iter::Iterator,
+    {
+        self.inner.try_next()
+    }
+}
+
+// Only shared access is handed out: swapping the inner iterator with
+// one reading from another database would let it outlive that database.
+impl<K: Serializable + 'static, I> Deref for ArcIterator<K, I> {
+    type Target = I;
+
+    fn deref(&self) -> &I {
+        &self.inner
+    }
+}
+
+impl<K: Serializable + 'static, I: iter::Iterator> iter::Iterator for ArcIterator<K, I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+macro_rules! impl_strip_prefix {
+    ($T:ty, $Item:ty, |$inner:ident, $suffix:ident| $item:expr) => {
+        impl<'a, K: Serializable> $T {
+            /// Strip the prefix of a prefix iteration from the keys.
+            pub fn strip_prefix(self) -> StripPrefix<Self> {
+                StripPrefix {
+                    prefix_len: self.prefix_len(),
+                    inner: self,
+                }
+            }
+        }
+
+        impl<'a, K: Serializable> iter::Iterator for StripPrefix<$T> {
+            type Item = $Item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.inner.advance() && !self.inner.stopped() {
+                    let $inner = &self.inner;
+                    let $suffix = $inner.raw_key()[self.prefix_len..].to_vec();
+                    Some($item)
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
+impl_strip_prefix!(Iterator<'a, K>, (Vec<u8>, Vec<u8>), |inner, suffix| (
+    suffix,
+    inner.value()
+));
+impl_strip_prefix!(RevIterator<'a, K>, (Vec<u8>, Vec<u8>), |inner, suffix| (
+    suffix,
+    inner.value()
+));
+impl_strip_prefix!(KeyIterator<'a, K>, Vec<u8>, |inner, suffix| suffix);
+impl_strip_prefix!(RevKeyIterator<'a, K>, Vec<u8>, |inner, suffix| suffix);
+
+impl<'a, K, I> iter::Iterator for Fallible<'a, K, I>
+where
+    K: Serializable + 'a,
+    I: LevelDBIterator<'a, K> + iter::Iterator,
+{
+    type Item = Result<<I as iter::Iterator>::Item, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.inner.try_next() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -68,15 +68,53 @@ impl<K: Serializable> KV<K> for Database<K> {
         key: BK,
         value: &[u8],
     ) -> Result<(), Error> {
+        self.put_raw(options, &self.encode_key(key.borrow()), value)
+    }
+
+    /// delete a value from the database.
+    ///
+    /// The passed key will be compared using the comparator.
+    ///
+    /// The database will be synced to disc if `options.sync == true`. This is
+    /// NOT the default.
+    fn delete<BK: Borrow<K>>(&self, options: WriteOptions, key: BK) -> Result<(), Error> {
+        self.delete_raw(options, &self.encode_key(key.borrow()))
+    }
+
+    fn get_bytes<'a, BK: Borrow<K>>(
+        &self,
+        options: ReadOptions<'a, K>,
+        key: BK,
+    ) -> Result<Option<Bytes>, Error> {
+        self.get_raw(&options, &self.encode_key(key.borrow()))
+    }
+
+    fn get<'a, BK: Borrow<K>>(
+        &self,
+        options: ReadOptions<'a, K>,
+        key: BK,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_bytes(options, key).map(|val| val.map(Into::into))
+    }
+}
+
+// The operations on encoded keys, shared with `RawDatabase`.
+impl<K: Serializable> Database<K> {
+    pub(crate) fn put_raw(
+        &self,
+        options: WriteOptions,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), Error> {
+        self.check_poisoned()?;
         unsafe {
-            let k = key.borrow().as_u8();
             let mut error = ptr::null_mut();
             let c_writeoptions = c_writeoptions(options);
             leveldb_put(
                 self.database.ptr,
                 c_writeoptions,
-                k.as_ptr() as *mut c_char,
-                k.len() as size_t,
+                key.as_ptr() as *mut c_char,
+                key.len() as size_t,
                 value.as_ptr() as *mut c_char,
                 value.len() as size_t,
                 &mut error,
@@ -84,55 +122,51 @@ impl<K: Serializable> KV<K> for Database<K> {
             leveldb_writeoptions_destroy(c_writeoptions);
 
             if error == ptr::null_mut() {
-                Ok(())
+                // the comparator may have panicked during the write
+                self.check_poisoned()
             } else {
                 Err(Error::new_from_char(error))
             }
         }
     }
 
-    /// delete a value from the database.
-    ///
-    /// The passed key will be compared using the comparator.
-    ///
-    /// The database will be synced to disc if `options.sync == true`. This is
-    /// NOT the default.
-    fn delete<BK: Borrow<K>>(&self, options: WriteOptions, key: BK) -> Result<(), Error> {
+    pub(crate) fn delete_raw(&self, options: WriteOptions, key: &[u8]) -> Result<(), Error> {
+        self.check_poisoned()?;
         unsafe {
-            let k = key.borrow().as_u8();
             let mut error = ptr::null_mut();
             let c_writeoptions = c_writeoptions(options);
             leveldb_delete(
                 self.database.ptr,
                 c_writeoptions,
-                k.as_ptr() as *mut c_char,
-                k.len() as size_t,
+                key.as_ptr() as *mut c_char,
+                key.len() as size_t,
                 &mut error,
             );
             leveldb_writeoptions_destroy(c_writeoptions);
             if error == ptr::null_mut() {
-                Ok(())
+                // the comparator may have panicked during the write
+                self.check_poisoned()
             } else {
                 Err(Error::new_from_char(error))
             }
         }
     }
 
-    fn get_bytes<'a, BK: Borrow<K>>(
+    pub(crate) fn get_raw(
         &self,
-        options: ReadOptions<'a, K>,
-        key: BK,
+        options: &ReadOptions<'_, K>,
+        key: &[u8],
     ) -> Result<Option<Bytes>, Error> {
+        self.check_poisoned()?;
         unsafe {
-            let k = key.borrow().as_u8();
             let mut error = ptr::null_mut();
             let mut length: size_t = 0;
-            let c_readoptions = c_readoptions(&options);
+            let c_readoptions = c_readoptions(options);
             let result = leveldb_get(
                 self.database.ptr,
                 c_readoptions,
-                k.as_ptr() as *mut c_char,
-                k.len() as size_t,
+                key.as_ptr() as *mut c_char,
+                key.len() as size_t,
                 &mut length,
                 &mut error,
             );
@@ -145,12 +179,4 @@ impl<K: Serializable> KV<K> for Database<K> {
             }
         }
     }
-
-    fn get<'a, BK: Borrow<K>>(
-        &self,
-        options: ReadOptions<'a, K>,
-        key: BK,
-    ) -> Result<Option<Vec<u8>>, Error> {
-        self.get_bytes(options, key).map(|val| val.map(Into::into))
-    }
 }
@@ -1,8 +1,8 @@
 //! Management functions, e.g. for destroying and reparing a database.
+use crate::database::c_path;
 use crate::error::Error;
 use crate::options::{c_options, Options};
 use libc::c_char;
-use std::ffi::CString;
 use std::path::Path;
 use std::ptr;
 
@@ -11,8 +11,8 @@ use crate::binding::{leveldb_destroy_db, leveldb_repair_db};
 /// destroy a database. You shouldn't hold a handle on the database anywhere at that time.
 pub fn destroy(name: &Path, options: Options) -> Result<(), Error> {
     let mut error = ptr::null_mut();
+    let c_string = c_path(name)?;
     unsafe {
-        let c_string = CString::new(name.to_str().unwrap()).unwrap();
         let c_options = c_options(&options, None);
         leveldb_destroy_db(
             c_options,
@@ -31,8 +31,8 @@ pub fn destroy(name: &Path, options: Options) -> Result<(), Error> {
 /// repair the database. The database should be closed at this moment.
 pub fn repair(name: &Path, options: Options) -> Result<(), Error> {
     let mut error = ptr::null_mut();
+    let c_string = c_path(name)?;
     unsafe {
-        let c_string = CString::new(name.to_str().unwrap()).unwrap();
         let c_options = c_options(&options, None);
         leveldb_repair_db(
             c_options,
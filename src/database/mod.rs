@@ -2,37 +2,120 @@
 //! a key-value basis.
 use crate::binding::*;
 
-use self::error::Error;
+use self::error::{Error, ErrorKind};
 use self::options::{c_options, Options};
 use std::ffi::CString;
 
 use std::path::Path;
 
 use self::serializable::Serializable;
-use comparator::{create_comparator, Comparator};
+use comparator::{
+    create_ordered_comparator, Comparator, FnComparator, KeyOrder, RawFnComparator, TypedComparator,
+};
+use std::cmp::Ordering;
 use std::ptr;
 
-use libc::c_char;
+use libc::{c_char, c_int, size_t};
 use std::marker::PhantomData;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{Arc, Mutex};
 
+#[cfg(feature = "tokio")]
+pub mod async_database;
 pub mod batch;
 pub mod bytes;
 pub mod cache;
 pub mod compaction;
 pub mod comparator;
+#[doc(hidden)]
+#[cfg(feature = "derive")]
+pub mod derive_support;
 pub mod error;
+pub mod filter_policy;
 pub mod iterator;
 pub mod kv;
 pub mod management;
+pub mod namespace;
 pub mod options;
+pub mod properties;
+pub mod raw;
 pub mod serializable;
 pub mod snapshots;
+pub mod tuple;
+#[cfg(feature = "serde")]
+pub mod typed;
+
+/// Convert a database path into a C string, failing for paths leveldb can't take.
+fn c_path(name: &Path) -> Result<CString, Error> {
+    let name = name.to_str().ok_or_else(|| {
+        Error::with_kind(
+            ErrorKind::InvalidArgument,
+            format!("{}: path is not valid UTF-8", name.display()),
+        )
+    })?;
+    CString::new(name).map_err(|_| {
+        Error::with_kind(
+            ErrorKind::InvalidArgument,
+            format!("{}: path contains a NUL byte", name),
+        )
+    })
+}
+
+/// Check that a comparator name can be passed to leveldb.
+fn check_comparator_name(name: &str) -> Result<(), Error> {
+    if name.contains('\0') {
+        Err(Error::with_kind(
+            ErrorKind::InvalidArgument,
+            format!("{}: comparator name contains a NUL byte", name),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// The smallest key greater than all keys starting with `prefix`, if any.
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    // strip trailing 0xFF bytes and increment the last byte
+    let mut end = prefix.to_vec();
+    while end.last() == Some(&0xFF) {
+        end.pop();
+    }
+    let last = end.last_mut()?;
+    *last += 1;
+    Some(end)
+}
+
+fn poisoned_error() -> Error {
+    Error::with_kind(
+        ErrorKind::Poisoned,
+        "database is poisoned by a panic in a callback".to_string(),
+    )
+}
 
 #[allow(missing_docs)]
 struct RawDB {
     ptr: *mut leveldb_t,
+    // this holds a reference passed into leveldb
+    // it is never read from Rust, but must be kept around
+    // until the database is closed
+    #[allow(dead_code)]
+    comparator: Option<RawComparator>,
+    // these hold multiple references that are used by the leveldb library
+    // and should survive as long as the database lives
+    #[allow(dead_code)]
+    options: Options,
+    // serialises changes of the namespace metadata, see `namespace`
+    namespaces: Mutex<()>,
 }
 
+// SAFETY: leveldb synchronises all operations on a `leveldb_t` internally,
+// so the handle may be used from any thread, and by several at once. The
+// comparator and options are only handed to leveldb, which already uses
+// them from its background threads, and released in `drop` after the
+// database is closed.
+unsafe impl Send for RawDB {}
+unsafe impl Sync for RawDB {}
+
 #[allow(missing_docs)]
 impl Drop for RawDB {
     fn drop(&mut self) {
@@ -45,6 +128,8 @@ impl Drop for RawDB {
 #[allow(missing_docs)]
 struct RawComparator {
     ptr: *mut leveldb_comparator_t,
+    // whether the comparator decodes the keys, see `Database::raw`
+    typed: bool,
 }
 
 impl Drop for RawComparator {
@@ -68,18 +153,18 @@ impl Drop for RawComparator {
 /// Multiple Database objects can be kept around, as leveldb synchronises
 /// internally.
 pub struct Database<K: Serializable> {
-    database: RawDB,
-    // this holds a reference passed into leveldb
-    // it is never read from Rust, but must be kept around
-    #[allow(dead_code)]
-    comparator: Option<RawComparator>,
-    // these hold multiple references that are used by the leveldb library
-    // and should survive as long as the database lives
-    #[allow(dead_code)]
-    options: Options,
+    // shared with the views created by `raw` and `RawDatabase::view`
+    database: Arc<RawDB>,
+    // set when a callback passed into leveldb panicked
+    poisoned: Arc<AtomicBool>,
+    // the order of keys, used to check iterator bounds
+    order: KeyOrder,
+    // prepended to all keys by the views of a `Namespace`, empty otherwise
+    prefix: Vec<u8>,
     marker: PhantomData<K>,
 }
 
+// the keys are only encoded and decoded, never stored
 unsafe impl<K: Serializable> Sync for Database<K> {}
 unsafe impl<K: Serializable> Send for Database<K> {}
 
@@ -87,28 +172,53 @@ impl<K: Serializable> Database<K> {
     fn new(
         database: *mut leveldb_t,
         options: Options,
-        comparator: Option<*mut leveldb_comparator_t>,
+        comparator: Option<RawComparator>,
+        poisoned: Arc<AtomicBool>,
+        order: KeyOrder,
     ) -> Database<K> {
-        let raw_comp = match comparator {
-            Some(p) => Some(RawComparator { ptr: p }),
-            None => None,
-        };
         Database {
-            database: RawDB { ptr: database },
-            comparator: raw_comp,
-            options: options,
+            database: Arc::new(RawDB {
+                ptr: database,
+                comparator: comparator,
+                options: options,
+                namespaces: Mutex::new(()),
+            }),
+            poisoned: poisoned,
+            order: order,
+            prefix: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// A view of this database with keys of type `T`, prepending `prefix`
+    /// to all of them.
+    fn with_prefix<T: Serializable>(&self, prefix: Vec<u8>) -> Database<T> {
+        Database {
+            database: self.database.clone(),
+            poisoned: self.poisoned.clone(),
+            order: self.order,
+            prefix: prefix,
             marker: PhantomData,
         }
     }
 
+    /// Encode `key` as stored in leveldb.
+    fn encode_key(&self, key: &K) -> Vec<u8> {
+        if self.prefix.is_empty() {
+            key.as_u8()
+        } else {
+            [&self.prefix[..], &key.as_u8()].concat()
+        }
+    }
+
     /// Open a new database
     ///
     /// If the database is missing, the behaviour depends on `options.create_if_missing`.
     /// The database will be created using the settings given in `options`.
     pub fn open(name: &Path, options: Options) -> Result<Database<K>, Error> {
         let mut error = ptr::null_mut();
+        let c_string = c_path(name)?;
         unsafe {
-            let c_string = CString::new(name.to_str().unwrap()).unwrap();
             let c_options = c_options(&options, None);
             let db = leveldb_open(
                 c_options as *const leveldb_options_t,
@@ -118,7 +228,13 @@ impl<K: Serializable> Database<K> {
             leveldb_options_destroy(c_options);
 
             if error == ptr::null_mut() {
-                Ok(Database::new(db, options, None))
+                Ok(Database::new(
+                    db,
+                    options,
+                    None,
+                    Arc::new(AtomicBool::new(false)),
+                    KeyOrder::bytewise(),
+                ))
             } else {
                 Err(Error::new_from_char(error))
             }
@@ -137,11 +253,81 @@ impl<K: Serializable> Database<K> {
         name: &Path,
         options: Options,
         comparator: C,
+    ) -> Result<Database<K>, Error> {
+        Database::open_with_ordered_comparator(name, options, TypedComparator(comparator), true)
+    }
+
+    /// Open a new database with a comparator working on the encoded keys
+    ///
+    /// If the database is missing, the behaviour depends on `options.create_if_missing`.
+    /// The database will be created using the settings given in `options`.
+    ///
+    /// The comparator must implement a total ordering over the keyspace.
+    pub fn open_with_raw_comparator<C: comparator::RawComparator>(
+        name: &Path,
+        options: Options,
+        comparator: C,
+    ) -> Result<Database<K>, Error> {
+        Database::open_with_ordered_comparator(name, options, comparator, false)
+    }
+
+    /// Open a new database ordering keys with the closure `compare`
+    ///
+    /// `comparator_name` identifies the ordering, see `Comparator::name`.
+    /// It must not contain NUL bytes.
+    ///
+    /// The closure must implement a total ordering over the keyspace.
+    /// leveldb calls it from its background threads.
+    pub fn open_with_compare_fn<F>(
+        name: &Path,
+        options: Options,
+        comparator_name: &str,
+        compare: F,
+    ) -> Result<Database<K>, Error>
+    where
+        F: Fn(&K, &K) -> Ordering + Send + Sync + 'static,
+    {
+        check_comparator_name(comparator_name)?;
+        let comparator = FnComparator::new(comparator_name, compare);
+        Database::open_with_comparator(name, options, comparator)
+    }
+
+    /// Open a new database ordering the encoded keys with the closure `compare`
+    ///
+    /// `comparator_name` identifies the ordering, see `Comparator::name`.
+    /// It must not contain NUL bytes.
+    ///
+    /// The closure must implement a total ordering over the keyspace.
+    /// leveldb calls it from its background threads.
+    pub fn open_with_raw_compare_fn<F>(
+        name: &Path,
+        options: Options,
+        comparator_name: &str,
+        compare: F,
+    ) -> Result<Database<K>, Error>
+    where
+        F: Fn(&[u8], &[u8]) -> Ordering + Send + Sync + 'static,
+    {
+        check_comparator_name(comparator_name)?;
+        let comparator = RawFnComparator::new(comparator_name, compare);
+        Database::open_with_raw_comparator(name, options, comparator)
+    }
+
+    fn open_with_ordered_comparator<C: comparator::RawComparator>(
+        name: &Path,
+        options: Options,
+        comparator: C,
+        typed: bool,
     ) -> Result<Database<K>, Error> {
         let mut error = ptr::null_mut();
-        let comp_ptr = create_comparator(Box::new(comparator));
+        let poisoned = Arc::new(AtomicBool::new(false));
+        let (comp_ptr, order) = create_ordered_comparator(comparator, poisoned.clone());
+        let comparator = RawComparator {
+            ptr: comp_ptr,
+            typed: typed,
+        };
+        let c_string = c_path(name)?;
         unsafe {
-            let c_string = CString::new(name.to_str().unwrap()).unwrap();
             let c_options = c_options(&options, Some(comp_ptr));
             let db = leveldb_open(
                 c_options as *const leveldb_options_t,
@@ -151,10 +337,65 @@ impl<K: Serializable> Database<K> {
             leveldb_options_destroy(c_options);
 
             if error == ptr::null_mut() {
-                Ok(Database::new(db, options, Some(comp_ptr)))
+                let database = Database::new(db, options, Some(comparator), poisoned, order);
+                database.check_poisoned()?;
+                Ok(database)
             } else {
                 Err(Error::new_from_char(error))
             }
         }
     }
+
+    /// Whether a callback passed into leveldb, e.g. the comparator, panicked.
+    ///
+    /// A poisoned database returns an error of kind `ErrorKind::Poisoned`
+    /// for all further operations and should be closed.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(atomic::Ordering::SeqCst)
+    }
+
+    fn check_poisoned(&self) -> Result<(), Error> {
+        if self.is_poisoned() {
+            Err(poisoned_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Return the approximate file system space used by keys in each range.
+    ///
+    /// Each range is given as `(start, limit)`, including `start` and
+    /// excluding `limit`. The sizes are returned in the order of the ranges.
+    ///
+    /// Note that the result only reflects data flushed to disk, so recently
+    /// written data may not be accounted for.
+    pub fn approximate_sizes(&self, ranges: &[(K, K)]) -> Vec<u64> {
+        let starts: Vec<Vec<u8>> = ranges
+            .iter()
+            .map(|(start, _)| self.encode_key(start))
+            .collect();
+        let limits: Vec<Vec<u8>> = ranges
+            .iter()
+            .map(|(_, limit)| self.encode_key(limit))
+            .collect();
+        let start_ptrs: Vec<*const c_char> =
+            starts.iter().map(|k| k.as_ptr() as *const c_char).collect();
+        let start_lens: Vec<size_t> = starts.iter().map(|k| k.len() as size_t).collect();
+        let limit_ptrs: Vec<*const c_char> =
+            limits.iter().map(|k| k.as_ptr() as *const c_char).collect();
+        let limit_lens: Vec<size_t> = limits.iter().map(|k| k.len() as size_t).collect();
+        let mut sizes = vec![0u64; ranges.len()];
+        unsafe {
+            leveldb_approximate_sizes(
+                self.database.ptr,
+                ranges.len() as c_int,
+                start_ptrs.as_ptr(),
+                start_lens.as_ptr(),
+                limit_ptrs.as_ptr(),
+                limit_lens.as_ptr(),
+                sizes.as_mut_ptr(),
+            );
+        }
+        sizes
+    }
 }
@@ -0,0 +1,341 @@
+//! Namespaces, emulating column families with key prefixes.
+//!
+//! leveldb has a single keyspace. A `Namespace` is a view of a database
+//! that prepends a 4 byte id to all of its keys, so several kinds of
+//! records can be kept apart in the same database:
+//!
+//! ```rust,ignore
+//! let users: Namespace<u64> = Namespace::open(&database, "users")?;
+//! let sessions: Namespace<String> = Namespace::open(&database, "sessions")?;
+//! users.put(WriteOptions::new(), 1, b"alice")?;
+//! // iterates over the users only
+//! for (id, name) in users.iter(ReadOptions::new()) {}
+//! ```
+//!
+//! Keys, bounds and batches are prefixed transparently, and iteration,
+//! snapshots, compaction and `approximate_sizes` stay within the namespace.
+//! `list`, `rename` and `drop_namespace` manage the namespaces of a database.
+//!
+//! The ids of namespaces are kept in the reserved namespace with id 0,
+//! mapping their names to ids. Once a database uses namespaces, all of its
+//! keys should be stored in namespaces. Namespaces need the default bytewise
+//! order of keys, so they can't be used with a custom comparator.
+use super::batch::{Batch, Writebatch};
+use super::bytes::Bytes;
+use super::compaction::Compaction;
+use super::error::{Error, ErrorKind};
+use super::iterator::{Iterable, Iterator, KeyIterator, LevelDBIterator, RawCursor, ValueIterator};
+use super::kv::KV;
+use super::options::{ReadOptions, WriteOptions};
+use super::serializable::Serializable;
+use super::snapshots::{Snapshot, Snapshots};
+use super::{prefix_successor, Database};
+use std::borrow::Borrow;
+use std::ops::RangeBounds;
+use std::sync::MutexGuard;
+
+// the prefix of the reserved namespace holding the metadata
+const METADATA: [u8; 4] = [0; 4];
+// the metadata keys mapping names to ids
+const NAMES: u8 = 0x00;
+// the metadata key holding the next id to assign
+const NEXT_ID: u8 = 0x01;
+
+// Changes of the metadata read it first, so they must not interleave.
+// leveldb allows a single open handle per database, which all views share,
+// so a lock kept with the handle serialises all changes of its metadata.
+fn lock_metadata<T: Serializable>(database: &Database<T>) -> MutexGuard<'_, ()> {
+    database
+        .database
+        .namespaces
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn name_key(name: &str) -> Vec<u8> {
+    [&METADATA[..], &[NAMES], name.as_bytes()].concat()
+}
+
+fn decode_id(bytes: &[u8]) -> Result<u32, Error> {
+    let bytes: [u8; 4] = bytes.try_into().map_err(|_| {
+        Error::with_kind(ErrorKind::Corruption, "malformed namespace id".to_string())
+    })?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn not_found(name: &str) -> Error {
+    Error::with_kind(ErrorKind::NotFound, format!("{}: no such namespace", name))
+}
+
+/// The whole database, with untyped keys.
+fn metadata<T: Serializable>(database: &Database<T>) -> Result<Database<Vec<u8>>, Error> {
+    if database.database.comparator.is_some() {
+        return Err(Error::with_kind(
+            ErrorKind::InvalidArgument,
+            "namespaces need the default bytewise comparator".to_string(),
+        ));
+    }
+    Ok(database.with_prefix(Vec::new()))
+}
+
+fn lookup(metadata: &Database<Vec<u8>>, name: &str) -> Result<Option<u32>, Error> {
+    match metadata.get_raw(&ReadOptions::new(), &name_key(name))? {
+        Some(bytes) => decode_id(&bytes).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// The names and ids of all namespaces of `database`, ordered by name.
+pub fn list<T: Serializable>(database: &Database<T>) -> Result<Vec<(String, u32)>, Error> {
+    let metadata = metadata(database)?;
+    let prefix = [&METADATA[..], &[NAMES]].concat();
+    let mut iter = metadata.prefix_iter(ReadOptions::new(), &prefix);
+    let mut namespaces = vec![];
+    while let Some((key, value)) = iter.try_next()? {
+        let name = String::from_utf8(key[prefix.len()..].to_vec()).map_err(|_| {
+            Error::with_kind(
+                ErrorKind::Corruption,
+                "malformed namespace name".to_string(),
+            )
+        })?;
+        namespaces.push((name, decode_id(&value)?));
+    }
+    Ok(namespaces)
+}
+
+/// Rename the namespace `from` of `database` to `to`.
+///
+/// The keys of the namespace are kept. Returns an error of kind
+/// `ErrorKind::NotFound` if `from` is missing, and of kind
+/// `ErrorKind::InvalidArgument` if `to` exists.
+pub fn rename<T: Serializable>(database: &Database<T>, from: &str, to: &str) -> Result<(), Error> {
+    let metadata = metadata(database)?;
+    let _lock = lock_metadata(database);
+    let id = lookup(&metadata, from)?.ok_or_else(|| not_found(from))?;
+    if lookup(&metadata, to)?.is_some() {
+        return Err(Error::with_kind(
+            ErrorKind::InvalidArgument,
+            format!("{}: namespace exists", to),
+        ));
+    }
+    let mut batch = Writebatch::new();
+    batch.delete(name_key(from));
+    batch.put(name_key(to), &id.to_be_bytes());
+    metadata.write(WriteOptions::new(), &batch)
+}
+
+/// Delete the namespace `name` of `database` with all of its keys.
+///
+/// Ids are never reused, handles of the dropped namespace must not be
+/// used anymore. Returns an error of kind `ErrorKind::NotFound` if the
+/// namespace is missing.
+pub fn drop_namespace<T: Serializable>(database: &Database<T>, name: &str) -> Result<(), Error> {
+    let metadata = metadata(database)?;
+    let _lock = lock_metadata(database);
+    let id = lookup(&metadata, name)?.ok_or_else(|| not_found(name))?;
+    let prefix = id.to_be_bytes();
+    // delete the keys first, so a failure leaves the namespace in place
+    let mut iter = metadata.prefix_keys(ReadOptions::new(), &prefix);
+    let mut batch = Writebatch::new();
+    let mut pending = 0;
+    while let Some(key) = iter.try_next()? {
+        batch.delete(key);
+        pending += 1;
+        if pending == 1000 {
+            metadata.write(WriteOptions::new(), &batch)?;
+            batch.clear();
+            pending = 0;
+        }
+    }
+    batch.delete(name_key(name));
+    metadata.write(WriteOptions::new(), &batch)?;
+    let start = prefix.to_vec();
+    let limit = prefix_successor(&prefix);
+    metadata.compact_range(Some(&start), limit.as_ref());
+    Ok(())
+}
+
+/// A view of the keys of a database within a namespace.
+///
+/// Handles are cheap and share the leveldb handle of the database they
+/// were opened from, which they keep open.
+pub struct Namespace<K: Serializable> {
+    database: Database<K>,
+    name: String,
+    id: u32,
+}
+
+impl<K: Serializable> Namespace<K> {
+    /// Open the namespace `name` of `database`, creating it if it is missing.
+    pub fn open<T: Serializable>(
+        database: &Database<T>,
+        name: &str,
+    ) -> Result<Namespace<K>, Error> {
+        let metadata = metadata(database)?;
+        let _lock = lock_metadata(database);
+        let id = match lookup(&metadata, name)? {
+            Some(id) => id,
+            None => {
+                let next_key = [&METADATA[..], &[NEXT_ID]].concat();
+                let id = match metadata.get_raw(&ReadOptions::new(), &next_key)? {
+                    Some(bytes) => decode_id(&bytes)?,
+                    None => 1,
+                };
+                let next = id.checked_add(1).ok_or_else(|| {
+                    Error::with_kind(
+                        ErrorKind::InvalidArgument,
+                        "all namespace ids are used".to_string(),
+                    )
+                })?;
+                let mut batch = Writebatch::new();
+                batch.put(name_key(name), &id.to_be_bytes());
+                batch.put(next_key, &next.to_be_bytes());
+                metadata.write(WriteOptions::new(), &batch)?;
+                id
+            }
+        };
+        Ok(Namespace::new(database, name.to_string(), id))
+    }
+
+    /// Open the namespace with the given `id`.
+    ///
+    /// Returns an error of kind `ErrorKind::NotFound` if there is none.
+    pub fn open_id<T: Serializable>(
+        database: &Database<T>,
+        id: u32,
+    ) -> Result<Namespace<K>, Error> {
+        let name = list(database)?
+            .into_iter()
+            .find(|(_, namespace_id)| *namespace_id == id)
+            .map(|(name, _)| name)
+            .ok_or_else(|| not_found(&id.to_string()))?;
+        Ok(Namespace::new(database, name, id))
+    }
+
+    fn new<T: Serializable>(database: &Database<T>, name: String, id: u32) -> Namespace<K> {
+        Namespace {
+            database: database.with_prefix(id.to_be_bytes().to_vec()),
+            name: name,
+            id: id,
+        }
+    }
+
+    /// The name this namespace was opened with.
+    ///
+    /// `rename` doesn't update the names of open namespaces, use `list` to
+    /// get the current name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The id prefixed to the keys of this namespace.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The view of the database prefixing keys with the id of this namespace.
+    pub fn database(&self) -> &Database<K> {
+        &self.database
+    }
+
+    /// Return the approximate file system space used by keys in each range.
+    ///
+    /// See `Database::approximate_sizes`.
+    pub fn approximate_sizes(&self, ranges: &[(K, K)]) -> Vec<u64> {
+        self.database.approximate_sizes(ranges)
+    }
+}
+
+impl<K: Serializable> KV<K> for Namespace<K> {
+    fn get<'a, BK: Borrow<K>>(
+        &self,
+        options: ReadOptions<'a, K>,
+        key: BK,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.database.get(options, key)
+    }
+
+    fn get_bytes<'a, BK: Borrow<K>>(
+        &self,
+        options: ReadOptions<'a, K>,
+        key: BK,
+    ) -> Result<Option<Bytes>, Error> {
+        self.database.get_bytes(options, key)
+    }
+
+    fn put<BK: Borrow<K>>(
+        &self,
+        options: WriteOptions,
+        key: BK,
+        value: &[u8],
+    ) -> Result<(), Error> {
+        self.database.put(options, key, value)
+    }
+
+    fn delete<BK: Borrow<K>>(&self, options: WriteOptions, key: BK) -> Result<(), Error> {
+        self.database.delete(options, key)
+    }
+}
+
+impl<K: Serializable> Batch<K> for Namespace<K> {
+    fn write(&self, options: WriteOptions, batch: &Writebatch<K>) -> Result<(), Error> {
+        self.database.write(options, batch)
+    }
+}
+
+impl<K: Serializable> Snapshots<K> for Namespace<K> {
+    fn snapshot<'a>(&'a self) -> Snapshot<'a, K> {
+        self.database.snapshot()
+    }
+}
+
+impl<'a, K: Serializable + 'a> Compaction<'a, K> for Namespace<K> {
+    fn compact_range(&self, start: Option<&'a K>, limit: Option<&'a K>) {
+        self.database.compact_range(start, limit)
+    }
+}
+
+impl<'a, K: Serializable + 'a> Iterable<'a, K> for Namespace<K> {
+    fn iter(&'a self, options: ReadOptions<'a, K>) -> Iterator<'a, K> {
+        self.database.iter(options)
+    }
+    fn keys_iter(&'a self, options: ReadOptions<'a, K>) -> KeyIterator<'a, K> {
+        self.database.keys_iter(options)
+    }
+    fn value_iter(&'a self, options: ReadOptions<'a, K>) -> ValueIterator<'a, K> {
+        self.database.value_iter(options)
+    }
+    fn range<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> Iterator<'a, K> {
+        self.database.range(options, range)
+    }
+    fn range_keys<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> KeyIterator<'a, K> {
+        self.database.range_keys(options, range)
+    }
+    fn range_values<R: RangeBounds<K>>(
+        &'a self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> ValueIterator<'a, K> {
+        self.database.range_values(options, range)
+    }
+    fn cursor(&'a self, options: ReadOptions<'a, K>) -> RawCursor<'a, K> {
+        self.database.cursor(options)
+    }
+    fn prefix_iter(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> Iterator<'a, K> {
+        self.database.prefix_iter(options, prefix)
+    }
+    fn prefix_keys(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> KeyIterator<'a, K> {
+        self.database.prefix_keys(options, prefix)
+    }
+    fn prefix_values(&'a self, options: ReadOptions<'a, K>, prefix: &[u8]) -> ValueIterator<'a, K> {
+        self.database.prefix_values(options, prefix)
+    }
+}
@@ -7,6 +7,7 @@
 use crate::binding::*;
 
 use crate::database::cache::Cache;
+use crate::database::filter_policy::FilterPolicy;
 use crate::database::serializable::Serializable;
 use crate::database::snapshots::Snapshot;
 use libc::size_t;
@@ -56,6 +57,10 @@ pub struct Options {
     ///
     /// default: None
     pub cache: Option<Cache>,
+    /// A filter policy to reduce disk reads, e.g. a bloom filter.
+    ///
+    /// default: None
+    pub filter_policy: Option<FilterPolicy>,
 }
 
 impl Options {
@@ -71,6 +76,7 @@ impl Options {
             block_restart_interval: None,
             compression: Compression::No,
             cache: None,
+            filter_policy: None,
         }
     }
 }
@@ -151,6 +157,9 @@ pub unsafe fn c_options(
     if let Some(ref cache) = options.cache {
         leveldb_options_set_cache(c_options, cache.raw_ptr());
     }
+    if let Some(ref policy) = options.filter_policy {
+        leveldb_options_set_filter_policy(c_options, policy.raw_ptr());
+    }
     c_options
 }
 
@@ -0,0 +1,191 @@
+//! Database properties
+//!
+//! leveldb exposes internal state of a database through named properties.
+//! This module provides typed access to the well-known ones.
+use super::serializable::Serializable;
+use super::Database;
+use crate::binding::{leveldb_free, leveldb_property_value};
+use libc::{c_char, c_void};
+use std::ffi::{CStr, CString};
+
+/// Compaction statistics of a single level, as reported by `leveldb.stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelStats {
+    /// The level these statistics describe
+    pub level: usize,
+    /// Number of table files at this level
+    pub files: usize,
+    /// Total size of the table files, in MB
+    pub size_mb: f64,
+    /// Time spent compacting into this level, in seconds
+    pub compaction_time_secs: f64,
+    /// Data read by compactions into this level, in MB
+    pub read_mb: f64,
+    /// Data written by compactions into this level, in MB
+    pub write_mb: f64,
+}
+
+/// The parsed contents of the `leveldb.stats` property.
+///
+/// leveldb only reports levels that contain files or had compactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    /// Statistics per reported level, in ascending level order
+    pub levels: Vec<LevelStats>,
+}
+
+/// A table file, as reported by `leveldb.sstables`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SSTable {
+    /// The level the table lives at
+    pub level: usize,
+    /// The file number of the table
+    pub number: u64,
+    /// The size of the table file, in bytes
+    pub size: u64,
+    /// Debug representation of the smallest internal key in the table
+    pub smallest: String,
+    /// Debug representation of the largest internal key in the table
+    pub largest: String,
+}
+
+/// Access to the properties leveldb reports about a database.
+///
+/// All accessors return `None` if leveldb doesn't know the property.
+pub trait Properties {
+    /// Return the raw value of the property `name`.
+    fn property(&self, name: &str) -> Option<String>;
+
+    /// Return the number of table files at `level` (`leveldb.num-files-at-level<N>`).
+    fn num_files_at_level(&self, level: usize) -> Option<usize> {
+        self.property(&format!("leveldb.num-files-at-level{}", level))
+            .and_then(|v| v.trim().parse().ok())
+    }
+
+    /// Return the per-level compaction statistics (`leveldb.stats`).
+    fn stats(&self) -> Option<Stats> {
+        self.property("leveldb.stats").map(|v| parse_stats(&v))
+    }
+
+    /// Return the table files making up the database (`leveldb.sstables`).
+    fn sstables(&self) -> Option<Vec<SSTable>> {
+        self.property("leveldb.sstables")
+            .map(|v| parse_sstables(&v))
+    }
+
+    /// Return the approximate number of bytes of memory in use by the
+    /// database (`leveldb.approximate-memory-usage`).
+    fn approximate_memory_usage(&self) -> Option<u64> {
+        self.property("leveldb.approximate-memory-usage")
+            .and_then(|v| v.trim().parse().ok())
+    }
+}
+
+impl<K: Serializable> Properties for Database<K> {
+    fn property(&self, name: &str) -> Option<String> {
+        let name = CString::new(name).ok()?;
+        unsafe {
+            let value = leveldb_property_value(
+                self.database.ptr,
+                name.as_bytes_with_nul().as_ptr() as *const c_char,
+            );
+            if value.is_null() {
+                None
+            } else {
+                let result = CStr::from_ptr(value).to_string_lossy().into_owned();
+                leveldb_free(value as *mut c_void);
+                Some(result)
+            }
+        }
+    }
+}
+
+// The table rows look like:
+//
+// ```text
+//   0        1        0         0        0         0
+// ```
+//
+// Header lines don't start with a number and are skipped.
+fn parse_stats(value: &str) -> Stats {
+    let levels = value
+        .lines()
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if columns.len() != 6 {
+                return None;
+            }
+            Some(LevelStats {
+                level: columns[0].parse().ok()?,
+                files: columns[1].parse().ok()?,
+                size_mb: columns[2].parse().ok()?,
+                compaction_time_secs: columns[3].parse().ok()?,
+                read_mb: columns[4].parse().ok()?,
+                write_mb: columns[5].parse().ok()?,
+            })
+        })
+        .collect();
+    Stats { levels }
+}
+
+// The listing looks like:
+//
+// ```text
+// --- level 0 ---
+//  5:130['a' @ 1 : 1 .. 'b' @ 2 : 1]
+// --- level 1 ---
+// ```
+fn parse_sstables(value: &str) -> Vec<SSTable> {
+    let mut level = 0;
+    let mut tables = vec![];
+    for line in value.lines() {
+        if let Some(l) = line
+            .strip_prefix("--- level ")
+            .and_then(|rest| rest.strip_suffix(" ---"))
+        {
+            if let Ok(l) = l.parse() {
+                level = l;
+            }
+            continue;
+        }
+        if let Some(table) = parse_sstable(level, line) {
+            tables.push(table);
+        }
+    }
+    tables
+}
+
+// User keys are printed unescaped apart from non-printable bytes, so they
+// may contain " .. " themselves. The keys are split after the sequence
+// number and type following the `' @ ` that closes the smallest key.
+fn parse_sstable(level: usize, line: &str) -> Option<SSTable> {
+    let (number, rest) = line.trim_start().split_once(':')?;
+    let (size, rest) = rest.split_once('[')?;
+    let keys = rest.strip_suffix(']')?;
+    let (smallest, largest) = keys
+        .match_indices(" .. ")
+        .map(|(i, delimiter)| (&keys[..i], &keys[i + delimiter.len()..]))
+        .find(|(smallest, largest)| is_internal_key(smallest) && is_internal_key(largest))?;
+    Some(SSTable {
+        level,
+        number: number.parse().ok()?,
+        size: size.parse().ok()?,
+        smallest: smallest.to_string(),
+        largest: largest.to_string(),
+    })
+}
+
+// Whether `key` looks like `'user key' @ sequence : type`.
+fn is_internal_key(key: &str) -> bool {
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match key
+        .strip_prefix('\'')
+        .and_then(|key| key.rsplit_once("' @ "))
+    {
+        Some((_, tag)) => match tag.split_once(" : ") {
+            Some((sequence, kind)) => is_number(sequence) && is_number(kind),
+            None => false,
+        },
+        None => false,
+    }
+}
@@ -0,0 +1,218 @@
+//! Access to leveldb with untyped byte keys.
+//!
+//! A `Database<K>` encodes all of its keys as `K`. Databases holding several
+//! kinds of records, e.g. under different key prefixes, can be opened as a
+//! `RawDatabase` instead, which takes keys as `&[u8]`. Typed views are
+//! created with `view`, for each key type needed:
+//!
+//! ```rust,ignore
+//! let raw = RawDatabase::open(path, options)?;
+//! let users: Database<(u8, u64)> = raw.view();
+//! let names: Database<String> = raw.view();
+//! raw.put(WriteOptions::new(), b"version", &[1])?;
+//! ```
+//!
+//! Views share the open leveldb handle, its options and comparator, and
+//! keep them alive, so creating them is cheap and they may outlive the
+//! `RawDatabase`. Conversely, `Database::raw` returns untyped access to a
+//! typed database, unless its comparator decodes the keys.
+use super::batch::{Batch, Writebatch};
+use super::bytes::Bytes;
+use super::comparator::RawComparator;
+use super::error::{Error, ErrorKind};
+use super::iterator::{Iterable, Iterator, KeyIterator, RawCursor, ValueIterator};
+use super::options::{Options, ReadOptions, WriteOptions};
+use super::serializable::Serializable;
+use super::snapshots::{Snapshot, Snapshots};
+use super::Database;
+use std::cmp::Ordering;
+use std::ops::RangeBounds;
+use std::path::Path;
+
+/// A database taking its keys as byte slices.
+///
+/// Iteration and snapshots yield the keys as `Vec<u8>`.
+pub struct RawDatabase {
+    database: Database<Vec<u8>>,
+}
+
+impl RawDatabase {
+    /// Open a new database
+    ///
+    /// If the database is missing, the behaviour depends on `options.create_if_missing`.
+    /// The database will be created using the settings given in `options`.
+    pub fn open(name: &Path, options: Options) -> Result<RawDatabase, Error> {
+        Database::open(name, options).map(|database| RawDatabase { database: database })
+    }
+
+    /// Open a new database with a comparator working on the keys
+    ///
+    /// If the database is missing, the behaviour depends on `options.create_if_missing`.
+    /// The database will be created using the settings given in `options`.
+    ///
+    /// The comparator must implement a total ordering over the keyspace.
+    pub fn open_with_comparator<C: RawComparator>(
+        name: &Path,
+        options: Options,
+        comparator: C,
+    ) -> Result<RawDatabase, Error> {
+        Database::open_with_raw_comparator(name, options, comparator)
+            .map(|database| RawDatabase { database: database })
+    }
+
+    /// Open a new database ordering the keys with the closure `compare`
+    ///
+    /// `comparator_name` identifies the ordering, see `Comparator::name`.
+    /// It must not contain NUL bytes.
+    ///
+    /// The closure must implement a total ordering over the keyspace.
+    /// leveldb calls it from its background threads.
+    pub fn open_with_compare_fn<F>(
+        name: &Path,
+        options: Options,
+        comparator_name: &str,
+        compare: F,
+    ) -> Result<RawDatabase, Error>
+    where
+        F: Fn(&[u8], &[u8]) -> Ordering + Send + Sync + 'static,
+    {
+        Database::open_with_raw_compare_fn(name, options, comparator_name, compare)
+            .map(|database| RawDatabase { database: database })
+    }
+
+    /// A view of this database encoding its keys as `K`.
+    ///
+    /// The view shares the leveldb handle with this database. A
+    /// `RawDatabase` never has a comparator decoding its keys, so keys of
+    /// any type can be written through its views.
+    pub fn view<K: Serializable>(&self) -> Database<K> {
+        self.database.with_prefix(Vec::new())
+    }
+
+    /// Whether a callback passed into leveldb, e.g. the comparator, panicked.
+    pub fn is_poisoned(&self) -> bool {
+        self.database.is_poisoned()
+    }
+
+    /// get a value from the database.
+    pub fn get(
+        &self,
+        options: ReadOptions<'_, Vec<u8>>,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.get_bytes(options, key).map(|val| val.map(Into::into))
+    }
+
+    /// get a value from the database, without copying it out of the buffer
+    /// allocated by leveldb.
+    pub fn get_bytes(
+        &self,
+        options: ReadOptions<'_, Vec<u8>>,
+        key: &[u8],
+    ) -> Result<Option<Bytes>, Error> {
+        self.database.get_raw(&options, key)
+    }
+
+    /// put a binary value into the database.
+    ///
+    /// If the key is already present in the database, it will be overwritten.
+    pub fn put(&self, options: WriteOptions, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.database.put_raw(options, key, value)
+    }
+
+    /// delete a value from the database.
+    pub fn delete(&self, options: WriteOptions, key: &[u8]) -> Result<(), Error> {
+        self.database.delete_raw(options, key)
+    }
+}
+
+impl<K: Serializable> Database<K> {
+    /// Untyped access to this database.
+    ///
+    /// The returned database shares the leveldb handle with this one. For
+    /// the view of a `Namespace`, it accesses the whole database.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidArgument` if the database
+    /// was opened with a `Comparator`, which can only compare keys of type
+    /// `K`. Databases opened with a `RawComparator` are supported.
+    pub fn raw(&self) -> Result<RawDatabase, Error> {
+        if self.database.comparator.as_ref().is_some_and(|c| c.typed) {
+            return Err(Error::with_kind(
+                ErrorKind::InvalidArgument,
+                "the comparator of the database decodes its keys".to_string(),
+            ));
+        }
+        Ok(RawDatabase {
+            database: self.with_prefix(Vec::new()),
+        })
+    }
+}
+
+impl Batch<Vec<u8>> for RawDatabase {
+    fn write(&self, options: WriteOptions, batch: &Writebatch<Vec<u8>>) -> Result<(), Error> {
+        self.database.write(options, batch)
+    }
+}
+
+impl Snapshots<Vec<u8>> for RawDatabase {
+    fn snapshot<'a>(&'a self) -> Snapshot<'a, Vec<u8>> {
+        self.database.snapshot()
+    }
+}
+
+impl<'a> Iterable<'a, Vec<u8>> for RawDatabase {
+    fn iter(&'a self, options: ReadOptions<'a, Vec<u8>>) -> Iterator<'a, Vec<u8>> {
+        self.database.iter(options)
+    }
+    fn keys_iter(&'a self, options: ReadOptions<'a, Vec<u8>>) -> KeyIterator<'a, Vec<u8>> {
+        self.database.keys_iter(options)
+    }
+    fn value_iter(&'a self, options: ReadOptions<'a, Vec<u8>>) -> ValueIterator<'a, Vec<u8>> {
+        self.database.value_iter(options)
+    }
+    fn range<R: RangeBounds<Vec<u8>>>(
+        &'a self,
+        options: ReadOptions<'a, Vec<u8>>,
+        range: R,
+    ) -> Iterator<'a, Vec<u8>> {
+        self.database.range(options, range)
+    }
+    fn range_keys<R: RangeBounds<Vec<u8>>>(
+        &'a self,
+        options: ReadOptions<'a, Vec<u8>>,
+        range: R,
+    ) -> KeyIterator<'a, Vec<u8>> {
+        self.database.range_keys(options, range)
+    }
+    fn range_values<R: RangeBounds<Vec<u8>>>(
+        &'a self,
+        options: ReadOptions<'a, Vec<u8>>,
+        range: R,
+    ) -> ValueIterator<'a, Vec<u8>> {
+        self.database.range_values(options, range)
+    }
+    fn cursor(&'a self, options: ReadOptions<'a, Vec<u8>>) -> RawCursor<'a, Vec<u8>> {
+        self.database.cursor(options)
+    }
+    fn prefix_iter(
+        &'a self,
+        options: ReadOptions<'a, Vec<u8>>,
+        prefix: &[u8],
+    ) -> Iterator<'a, Vec<u8>> {
+        self.database.prefix_iter(options, prefix)
+    }
+    fn prefix_keys(
+        &'a self,
+        options: ReadOptions<'a, Vec<u8>>,
+        prefix: &[u8],
+    ) -> KeyIterator<'a, Vec<u8>> {
+        self.database.prefix_keys(options, prefix)
+    }
+    fn prefix_values(
+        &'a self,
+        options: ReadOptions<'a, Vec<u8>>,
+        prefix: &[u8],
+    ) -> ValueIterator<'a, Vec<u8>> {
+        self.database.prefix_values(options, prefix)
+    }
+}
@@ -1,25 +1,229 @@
+//! Conversion of keys from and to their binary representation.
+//!
+//! By default, leveldb orders keys by their binary value. The
+//! implementations provided here are order-preserving: the encoded
+//! bytes of two values compare the same way as the values themselves,
+//! so iteration order matches `Ord` without a custom comparator.
+//!
+//! * unsigned integers are encoded big-endian
+//! * signed integers are encoded big-endian with the sign bit flipped,
+//!   so negative numbers sort before positive ones
+//! * `bool` is encoded as a single byte, `char` as a big-endian `u32`
+//! * `String`, `Vec<u8>` and `[u8; N]` are stored as their raw bytes
+//!
+//! Note that versions before 0.9 stored `i32` keys without flipping the
+//! sign bit. Databases written by them can still be opened with
+//! `LegacyI32` keys, and migrated by copying their entries:
+//!
+//! ```rust,ignore
+//! let old: Database<LegacyI32> = Database::open(old_path, options)?;
+//! let new: Database<i32> = Database::open(new_path, options)?;
+//! for (key, value) in old.iter(ReadOptions::new()) {
+//!     new.put(WriteOptions::new(), key.0, &value)?;
+//! }
+//! ```
+//!
+//! Keys read back from the database may be malformed, e.g. because they were
+//! written with a different key type. `try_from_u8` reports those as errors,
+//! and is used wherever the library decodes keys on its own.
+use super::error::{Error, ErrorKind};
+use std::panic;
+
+/// Derive `Serializable` for structs and enums, see the `leveldb_derive`
+/// crate for the encoding and the supported field attributes.
+#[cfg(feature = "derive")]
+pub use leveldb_derive::Serializable;
+
 pub trait Serializable {
     fn from_u8(key: &[u8]) -> Self;
     fn as_u8(&self) -> Vec<u8>;
+
+    /// Decode a key, returning an error if `key` is malformed.
+    ///
+    /// The default implementation turns a panic in `from_u8` into an
+    /// error. Implementations should override it to validate `key`
+    /// without panicking.
+    fn try_from_u8(key: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        panic::catch_unwind(|| Self::from_u8(key))
+            .map_err(|_| malformed_key("failed to decode key".to_string()))
+    }
 }
 
 pub fn from_u8<K: Serializable>(key: &[u8]) -> K {
     Serializable::from_u8(key)
 }
 
-impl Serializable for i32 {
-    fn from_u8(key: &[u8]) -> i32 {
-        assert!(key.len() == 4);
+pub fn try_from_u8<K: Serializable>(key: &[u8]) -> Result<K, Error> {
+    Serializable::try_from_u8(key)
+}
+
+#[allow(missing_docs)]
+pub fn malformed_key(message: String) -> Error {
+    Error::with_kind(ErrorKind::Corruption, format!("malformed key: {}", message))
+}
+
+fn expect_len(key: &[u8], len: usize) -> Result<(), Error> {
+    if key.len() == len {
+        Ok(())
+    } else {
+        Err(malformed_key(format!(
+            "expected {} bytes, got {}",
+            len,
+            key.len()
+        )))
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($($T:ty),*) => {$(
+        impl Serializable for $T {
+            fn from_u8(key: &[u8]) -> $T {
+                Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            fn as_u8(&self) -> Vec<u8> {
+                self.to_be_bytes().to_vec()
+            }
+
+            fn try_from_u8(key: &[u8]) -> Result<$T, Error> {
+                expect_len(key, std::mem::size_of::<$T>())?;
+
+                let mut dst = [0u8; std::mem::size_of::<$T>()];
+                dst.copy_from_slice(key);
+                Ok(<$T>::from_be_bytes(dst))
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_signed {
+    ($($T:ty => $U:ty),*) => {$(
+        impl Serializable for $T {
+            fn from_u8(key: &[u8]) -> $T {
+                Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            fn as_u8(&self) -> Vec<u8> {
+                ((*self as $U) ^ (1 << (<$U>::BITS - 1))).as_u8()
+            }
+
+            fn try_from_u8(key: &[u8]) -> Result<$T, Error> {
+                Ok((<$U>::try_from_u8(key)? ^ (1 << (<$U>::BITS - 1))) as $T)
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128);
+impl_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+/// An `i32` key in the encoding used before 0.9: big-endian, without
+/// flipping the sign bit.
+///
+/// Negative keys sort after positive ones in this encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LegacyI32(pub i32);
+
+impl From<i32> for LegacyI32 {
+    fn from(value: i32) -> LegacyI32 {
+        LegacyI32(value)
+    }
+}
+
+impl From<LegacyI32> for i32 {
+    fn from(value: LegacyI32) -> i32 {
+        value.0
+    }
+}
+
+impl Serializable for LegacyI32 {
+    fn from_u8(key: &[u8]) -> LegacyI32 {
+        Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn as_u8(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+
+    fn try_from_u8(key: &[u8]) -> Result<LegacyI32, Error> {
+        Ok(LegacyI32(u32::try_from_u8(key)? as i32))
+    }
+}
+
+impl Serializable for bool {
+    fn from_u8(key: &[u8]) -> bool {
+        Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn as_u8(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    fn try_from_u8(key: &[u8]) -> Result<bool, Error> {
+        Ok(u8::try_from_u8(key)? != 0)
+    }
+}
+
+impl Serializable for char {
+    fn from_u8(key: &[u8]) -> char {
+        Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        (key[0] as i32) << 24 | (key[1] as i32) << 16 | (key[2] as i32) << 8 | (key[3] as i32)
+    fn as_u8(&self) -> Vec<u8> {
+        (*self as u32).as_u8()
+    }
+
+    fn try_from_u8(key: &[u8]) -> Result<char, Error> {
+        char::from_u32(u32::try_from_u8(key)?)
+            .ok_or_else(|| malformed_key("not a valid char".to_string()))
+    }
+}
+
+impl Serializable for String {
+    fn from_u8(key: &[u8]) -> String {
+        Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
     }
 
     fn as_u8(&self) -> Vec<u8> {
-        let mut dst = [0u8, 0, 0, 0];
-        dst[0] = (*self >> 24) as u8;
-        dst[1] = (*self >> 16) as u8;
-        dst[2] = (*self >> 8) as u8;
-        dst[3] = *self as u8;
-        dst.to_vec()
+        self.as_bytes().to_vec()
+    }
+
+    fn try_from_u8(key: &[u8]) -> Result<String, Error> {
+        String::from_utf8(key.to_vec()).map_err(|_| malformed_key("not valid UTF-8".to_string()))
+    }
+}
+
+impl Serializable for Vec<u8> {
+    fn from_u8(key: &[u8]) -> Vec<u8> {
+        key.to_vec()
+    }
+
+    fn as_u8(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn try_from_u8(key: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(key.to_vec())
+    }
+}
+
+impl<const N: usize> Serializable for [u8; N] {
+    fn from_u8(key: &[u8]) -> [u8; N] {
+        Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn as_u8(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn try_from_u8(key: &[u8]) -> Result<[u8; N], Error> {
+        expect_len(key, N)?;
+
+        let mut dst = [0u8; N];
+        dst.copy_from_slice(key);
+        Ok(dst)
     }
 }
@@ -2,6 +2,8 @@
 //!
 //! Snapshots give you a reference to the database at a certain
 //! point in time and won't change while you work with them.
+//!
+//! `OwnedSnapshot` holds an `Arc` of its database instead of borrowing it.
 use crate::binding::{leveldb_create_snapshot, leveldb_release_snapshot};
 use crate::binding::{leveldb_snapshot_t, leveldb_t};
 
@@ -10,11 +12,14 @@ use crate::database::serializable::Serializable;
 use crate::database::Database;
 
 use crate::database::error::Error;
-use crate::database::iterator::{Iterable, Iterator, KeyIterator, ValueIterator};
+use crate::database::iterator::{
+    ArcIterator, Iterable, Iterator, KeyIterator, RawCursor, ValueIterator,
+};
 use crate::database::options::ReadOptions;
 
 use std::borrow::Borrow;
-use std::cmp::Ord;
+use std::ops::RangeBounds;
+use std::sync::Arc;
 
 #[allow(missing_docs)]
 struct RawSnapshot {
@@ -28,6 +33,15 @@ impl Drop for RawSnapshot {
     }
 }
 
+// SAFETY: a leveldb snapshot is immutable once created; reads through it
+// only pass the pointer to leveldb, which synchronises internally just as
+// it does for reads without a snapshot. Releasing it takes the database
+// mutex, so it may happen on any thread. The database pointer is the same
+// one `Database` shares across threads, and the borrow (or `Arc`) held next
+// to the `RawSnapshot` keeps it open until the snapshot is released.
+unsafe impl Send for RawSnapshot {}
+unsafe impl Sync for RawSnapshot {}
+
 /// A database snapshot
 ///
 /// Represents a database at a certain point in time,
@@ -37,6 +51,17 @@ pub struct Snapshot<'a, K: Serializable + 'a> {
     database: &'a Database<K>,
 }
 
+/// A database snapshot holding a reference-counted database
+///
+/// Unlike `Snapshot`, it doesn't borrow the database and can be kept for
+/// as long as needed. Clones share the same leveldb snapshot.
+pub struct OwnedSnapshot<K: Serializable + 'static> {
+    // declared first, so the snapshot is released before the database
+    // may be closed
+    snapshot: Arc<Snapshot<'static, K>>,
+    database: Arc<Database<K>>,
+}
+
 /// Structs implementing the Snapshots trait can be
 /// snapshotted.
 pub trait Snapshots<K: Serializable> {
@@ -81,17 +106,120 @@ impl<'a, K: Serializable> Snapshot<'a, K> {
     }
 }
 
-impl<'a, K: Serializable + Ord + 'a> Iterable<'a, K> for Snapshot<'a, K> {
-    fn iter(&'a self, mut options: ReadOptions<'a, K>) -> Iterator<K> {
+impl<'a, K: Serializable + 'a> Iterable<'a, K> for Snapshot<'a, K> {
+    fn iter(&'a self, mut options: ReadOptions<'a, K>) -> Iterator<'a, K> {
         options.snapshot = Some(self);
         self.database.iter(options)
     }
-    fn keys_iter(&'a self, mut options: ReadOptions<'a, K>) -> KeyIterator<K> {
+    fn keys_iter(&'a self, mut options: ReadOptions<'a, K>) -> KeyIterator<'a, K> {
         options.snapshot = Some(self);
         self.database.keys_iter(options)
     }
-    fn value_iter(&'a self, mut options: ReadOptions<'a, K>) -> ValueIterator<K> {
+    fn value_iter(&'a self, mut options: ReadOptions<'a, K>) -> ValueIterator<'a, K> {
         options.snapshot = Some(self);
         self.database.value_iter(options)
     }
+    fn range<R: RangeBounds<K>>(
+        &'a self,
+        mut options: ReadOptions<'a, K>,
+        range: R,
+    ) -> Iterator<'a, K> {
+        options.snapshot = Some(self);
+        self.database.range(options, range)
+    }
+    fn range_keys<R: RangeBounds<K>>(
+        &'a self,
+        mut options: ReadOptions<'a, K>,
+        range: R,
+    ) -> KeyIterator<'a, K> {
+        options.snapshot = Some(self);
+        self.database.range_keys(options, range)
+    }
+    fn range_values<R: RangeBounds<K>>(
+        &'a self,
+        mut options: ReadOptions<'a, K>,
+        range: R,
+    ) -> ValueIterator<'a, K> {
+        options.snapshot = Some(self);
+        self.database.range_values(options, range)
+    }
+    fn cursor(&'a self, mut options: ReadOptions<'a, K>) -> RawCursor<'a, K> {
+        options.snapshot = Some(self);
+        self.database.cursor(options)
+    }
+    fn prefix_iter(&'a self, mut options: ReadOptions<'a, K>, prefix: &[u8]) -> Iterator<'a, K> {
+        options.snapshot = Some(self);
+        self.database.prefix_iter(options, prefix)
+    }
+    fn prefix_keys(&'a self, mut options: ReadOptions<'a, K>, prefix: &[u8]) -> KeyIterator<'a, K> {
+        options.snapshot = Some(self);
+        self.database.prefix_keys(options, prefix)
+    }
+    fn prefix_values(
+        &'a self,
+        mut options: ReadOptions<'a, K>,
+        prefix: &[u8],
+    ) -> ValueIterator<'a, K> {
+        options.snapshot = Some(self);
+        self.database.prefix_values(options, prefix)
+    }
+}
+
+impl<K: Serializable + 'static> OwnedSnapshot<K> {
+    /// Creates a snapshot of `database`.
+    pub fn new(database: Arc<Database<K>>) -> OwnedSnapshot<K> {
+        // The database lives on the heap and is kept alive by the
+        // OwnedSnapshot until after the snapshot is released, so the
+        // 'static borrow can't dangle.
+        let db: &'static Database<K> = unsafe { &*Arc::as_ptr(&database) };
+        OwnedSnapshot {
+            snapshot: Arc::new(db.snapshot()),
+            database: database,
+        }
+    }
+
+    /// The snapshot, borrowed for as long as this OwnedSnapshot.
+    ///
+    /// Use it to read from the snapshot.
+    pub fn as_snapshot(&self) -> &Snapshot<'_, K> {
+        &self.snapshot
+    }
+
+    /// The database this snapshot was taken from.
+    pub fn database(&self) -> &Arc<Database<K>> {
+        &self.database
+    }
+
+    /// fetches a key from the database
+    pub fn get<BK: Borrow<K>>(
+        &self,
+        options: ReadOptions<'_, K>,
+        key: BK,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.as_snapshot().get(options, key)
+    }
+
+    /// Return an ArcIterator iterating over the (Key,Value) pairs of this snapshot.
+    pub fn iter(&self, options: ReadOptions<'static, K>) -> ArcIterator<K> {
+        ArcIterator::snapshot(self.clone(), options)
+    }
+
+    /// Return an ArcIterator iterating over the (Key,Value) pairs of this
+    /// snapshot within `range`.
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        options: ReadOptions<'static, K>,
+        range: R,
+    ) -> ArcIterator<K> {
+        ArcIterator::snapshot_range(self.clone(), options, range)
+    }
+}
+
+impl<K: Serializable + 'static> Clone for OwnedSnapshot<K> {
+    fn clone(&self) -> OwnedSnapshot<K> {
+        OwnedSnapshot {
+            snapshot: self.snapshot.clone(),
+            database: self.database.clone(),
+        }
+    }
 }
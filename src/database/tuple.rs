@@ -0,0 +1,322 @@
+//! Order-preserving encoding of composite keys.
+//!
+//! Tuples of up to 8 elements implementing `TupleElement` are `Serializable`.
+//! The encoding is the concatenation of the encoded elements, in the spirit
+//! of the FoundationDB tuple layer, and sorts the same way the tuples do:
+//!
+//! * integers, `bool` and `char` use their fixed-width `Serializable` encoding
+//! * `String`, `Vec<u8>` and `[u8; N]` are escaped, `0x00` becoming
+//!   `0x00 0xFF`, and terminated by `0x00 0x01`
+//!
+//! Since every element is self-delimiting, the encoding of a partial tuple
+//! is a prefix of the encoding of all tuples starting with it. Use
+//! `prefix_range` to compute iteration bounds covering those tuples:
+//!
+//! ```rust,ignore
+//! let (from, to) = prefix_range::<(u32, String, u64), _>(&(7, "users".to_string()));
+//! let iter = database.iter(read_opts).from(&from);
+//! let iter = match to {
+//!     Some(ref to) => iter.to(to),
+//!     None => iter,
+//! };
+//! ```
+use super::error::Error;
+use super::serializable::{malformed_key, Serializable};
+use std::mem::size_of;
+
+const ESCAPE: u8 = 0x00;
+const ESCAPED_NUL: u8 = 0xFF;
+const TERMINATOR: u8 = 0x01;
+
+fn malformed(message: &str) -> Error {
+    malformed_key(format!("tuple: {}", message))
+}
+
+/// A type that can be used as an element of a tuple key.
+pub trait TupleElement: Sized + Clone {
+    /// Append the encoding of this element to `out`.
+    fn encode_into(&self, out: &mut Vec<u8>);
+    /// Decode an element from the front of `input`, advancing it.
+    fn decode_from(input: &mut &[u8]) -> Result<Self, Error>;
+    /// The smallest value of this type.
+    fn min_value() -> Self;
+    /// The smallest value greater than this one, if there is any.
+    fn successor(&self) -> Option<Self>;
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if input.len() < len {
+        return Err(malformed("unexpected end of key"));
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+macro_rules! impl_fixed_width_element {
+    ($($T:ty),*) => {$(
+        impl TupleElement for $T {
+            fn encode_into(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.as_u8());
+            }
+
+            fn decode_from(input: &mut &[u8]) -> Result<$T, Error> {
+                <$T>::try_from_u8(take(input, size_of::<$T>())?)
+            }
+
+            fn min_value() -> $T {
+                <$T>::MIN
+            }
+
+            fn successor(&self) -> Option<$T> {
+                self.checked_add(1)
+            }
+        }
+    )*};
+}
+
+impl_fixed_width_element!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl TupleElement for bool {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<bool, Error> {
+        match take(input, 1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(malformed("invalid bool")),
+        }
+    }
+
+    fn min_value() -> bool {
+        false
+    }
+
+    fn successor(&self) -> Option<bool> {
+        if *self {
+            None
+        } else {
+            Some(true)
+        }
+    }
+}
+
+impl TupleElement for char {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.as_u8());
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<char, Error> {
+        let code = u32::try_from_u8(take(input, 4)?)?;
+        char::from_u32(code).ok_or_else(|| malformed("invalid char"))
+    }
+
+    fn min_value() -> char {
+        '\0'
+    }
+
+    fn successor(&self) -> Option<char> {
+        match *self {
+            // skip the surrogate range, which holds no chars
+            '\u{D7FF}' => Some('\u{E000}'),
+            c => char::from_u32(c as u32 + 1),
+        }
+    }
+}
+
+fn encode_escaped(bytes: &[u8], out: &mut Vec<u8>) {
+    for &b in bytes {
+        out.push(b);
+        if b == ESCAPE {
+            out.push(ESCAPED_NUL);
+        }
+    }
+    out.push(ESCAPE);
+    out.push(TERMINATOR);
+}
+
+fn decode_escaped(input: &mut &[u8]) -> Result<Vec<u8>, Error> {
+    let mut result = vec![];
+    loop {
+        let b = take(input, 1)?[0];
+        if b != ESCAPE {
+            result.push(b);
+            continue;
+        }
+        match take(input, 1)?[0] {
+            ESCAPED_NUL => result.push(ESCAPE),
+            TERMINATOR => return Ok(result),
+            _ => return Err(malformed("invalid escape sequence")),
+        }
+    }
+}
+
+impl TupleElement for Vec<u8> {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        encode_escaped(self, out);
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<Vec<u8>, Error> {
+        decode_escaped(input)
+    }
+
+    fn min_value() -> Vec<u8> {
+        vec![]
+    }
+
+    fn successor(&self) -> Option<Vec<u8>> {
+        let mut next = self.clone();
+        next.push(0);
+        Some(next)
+    }
+}
+
+impl TupleElement for String {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        encode_escaped(self.as_bytes(), out);
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<String, Error> {
+        String::from_utf8(decode_escaped(input)?).map_err(|_| malformed("invalid UTF-8"))
+    }
+
+    fn min_value() -> String {
+        String::new()
+    }
+
+    fn successor(&self) -> Option<String> {
+        let mut next = self.clone();
+        next.push('\0');
+        Some(next)
+    }
+}
+
+impl<const N: usize> TupleElement for [u8; N] {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        encode_escaped(self, out);
+    }
+
+    fn decode_from(input: &mut &[u8]) -> Result<[u8; N], Error> {
+        let bytes = decode_escaped(input)?;
+        if bytes.len() != N {
+            return Err(malformed("byte array of wrong length"));
+        }
+        let mut dst = [0u8; N];
+        dst.copy_from_slice(&bytes);
+        Ok(dst)
+    }
+
+    fn min_value() -> [u8; N] {
+        [0u8; N]
+    }
+
+    fn successor(&self) -> Option<[u8; N]> {
+        let mut next = *self;
+        for b in next.iter_mut().rev() {
+            if *b == u8::MAX {
+                *b = 0;
+            } else {
+                *b += 1;
+                return Some(next);
+            }
+        }
+        None
+    }
+}
+
+/// A tuple key whose first elements can be given as a partial tuple `P`.
+pub trait PrefixRange<P>: Sized {
+    /// The smallest key starting with `prefix`.
+    fn prefix_start(prefix: &P) -> Self;
+    /// The smallest key greater than all keys starting with `prefix`,
+    /// or `None` if there is no such key.
+    fn prefix_end(prefix: &P) -> Option<Self>;
+}
+
+/// Compute the range of keys starting with the partial tuple `prefix`.
+///
+/// Returns the start (inclusive) and end (exclusive) of the range, suitable
+/// for `LevelDBIterator::from` and `LevelDBIterator::to`. The end is `None`
+/// if the range extends to the end of the keyspace.
+pub fn prefix_range<K: PrefixRange<P>, P>(prefix: &P) -> (K, Option<K>) {
+    (K::prefix_start(prefix), K::prefix_end(prefix))
+}
+
+macro_rules! impl_tuple {
+    ($($idx:tt $T:ident),+) => {
+        impl<$($T: TupleElement),+> Serializable for ($($T,)+) {
+            fn from_u8(key: &[u8]) -> Self {
+                Self::try_from_u8(key).unwrap_or_else(|e| panic!("{}", e))
+            }
+
+            fn as_u8(&self) -> Vec<u8> {
+                let mut out = vec![];
+                $(self.$idx.encode_into(&mut out);)+
+                out
+            }
+
+            fn try_from_u8(key: &[u8]) -> Result<Self, Error> {
+                let mut input = key;
+                let result = ($($T::decode_from(&mut input)?,)+);
+                if !input.is_empty() {
+                    return Err(malformed("trailing bytes"));
+                }
+                Ok(result)
+            }
+        }
+
+        impl_prefix_ranges!(($($idx $T),+); []; $($idx $T),+);
+    };
+}
+
+macro_rules! impl_prefix_ranges {
+    ($full:tt; [$($done:tt)*];) => {};
+    ($full:tt; [$($done:tt)*]; $idx:tt $T:ident $(, $ridx:tt $R:ident)*) => {
+        impl_prefix_range!($full; $($done)* $idx $T,);
+        impl_prefix_ranges!($full; [$($done)* $idx $T,]; $($ridx $R),*);
+    };
+}
+
+macro_rules! impl_prefix_range {
+    (($($idx:tt $T:ident),+); $($pidx:tt $P:ident,)+) => {
+        impl<$($T: TupleElement),+> PrefixRange<($($P,)+)> for ($($T,)+) {
+            fn prefix_start(prefix: &($($P,)+)) -> Self {
+                #[allow(unused_mut)]
+                let mut key = ($($T::min_value(),)+);
+                $(key.$pidx = prefix.$pidx.clone();)+
+                key
+            }
+
+            fn prefix_end(prefix: &($($P,)+)) -> Option<Self> {
+                let mut key = Self::prefix_start(prefix);
+                // Increment the last element of the prefix. If it has no
+                // successor, reset it and carry over to the element before.
+                let mut i = [$($pidx),+].len();
+                while i > 0 {
+                    i -= 1;
+                    $(
+                        if $idx == i {
+                            if let Some(next) = key.$idx.successor() {
+                                key.$idx = next;
+                                return Some(key);
+                            }
+                            key.$idx = $T::min_value();
+                        }
+                    )+
+                }
+                None
+            }
+        }
+    };
+}
+
+impl_tuple!(0 A);
+impl_tuple!(0 A, 1 B);
+impl_tuple!(0 A, 1 B, 2 C);
+impl_tuple!(0 A, 1 B, 2 C, 3 D);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
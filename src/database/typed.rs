@@ -0,0 +1,211 @@
+//! Typed values, enabled by the `serde` feature.
+//!
+//! `TypedDatabase` is a view over a `Database<K>` that stores values of
+//! type `V`, converting them with a `ValueCodec`. The codecs shipped are
+//! `Raw`, storing `Vec<u8>` as is, `Json` (feature `json`) and `Bincode`
+//! (feature `bincode`).
+//!
+//! Values that fail to decode are reported as errors of kind
+//! `ErrorKind::Corruption`.
+use super::error::Error;
+#[cfg(any(feature = "json", feature = "bincode"))]
+use super::error::ErrorKind;
+use super::iterator::{Iterable, Iterator, LevelDBIterator};
+use super::kv::KV;
+use super::options::{ReadOptions, WriteOptions};
+use super::serializable::Serializable;
+use super::Database;
+use std::borrow::Borrow;
+use std::iter;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+/// Converts values of type `V` to and from the bytes stored in leveldb.
+pub trait ValueCodec<V> {
+    /// Encode `value` for storage.
+    fn encode(value: &V) -> Result<Vec<u8>, Error>;
+    /// Decode a stored value.
+    fn decode(bytes: &[u8]) -> Result<V, Error>;
+}
+
+/// Stores byte vectors as they are.
+pub struct Raw;
+
+impl ValueCodec<Vec<u8>> for Raw {
+    fn encode(value: &Vec<u8>) -> Result<Vec<u8>, Error> {
+        Ok(value.clone())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Stores values as JSON.
+#[cfg(feature = "json")]
+pub struct Json;
+
+#[cfg(feature = "json")]
+impl<V: serde::Serialize + serde::de::DeserializeOwned> ValueCodec<V> for Json {
+    fn encode(value: &V) -> Result<Vec<u8>, Error> {
+        serde_json::to_vec(value).map_err(|e| encode_error(e.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<V, Error> {
+        serde_json::from_slice(bytes).map_err(|e| decode_error(e.to_string()))
+    }
+}
+
+/// Stores values in bincode's binary format.
+#[cfg(feature = "bincode")]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl<V: serde::Serialize + serde::de::DeserializeOwned> ValueCodec<V> for Bincode {
+    fn encode(value: &V) -> Result<Vec<u8>, Error> {
+        bincode::serialize(value).map_err(|e| encode_error(e.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<V, Error> {
+        bincode::deserialize(bytes).map_err(|e| decode_error(e.to_string()))
+    }
+}
+
+#[cfg(any(feature = "json", feature = "bincode"))]
+fn encode_error(message: String) -> Error {
+    Error::with_kind(
+        ErrorKind::InvalidArgument,
+        format!("failed to encode value: {}", message),
+    )
+}
+
+#[cfg(any(feature = "json", feature = "bincode"))]
+fn decode_error(message: String) -> Error {
+    Error::with_kind(
+        ErrorKind::Corruption,
+        format!("failed to decode value: {}", message),
+    )
+}
+
+/// A view over a database storing values of type `V`, encoded with `C`.
+pub struct TypedDatabase<'a, K: Serializable + 'a, V, C: ValueCodec<V>> {
+    database: &'a Database<K>,
+    marker: PhantomData<(V, C)>,
+}
+
+/// An iterator over the decoded (Key,Value) pairs of a `TypedDatabase`.
+///
+/// Yields an error as the final item if a key or value can't be decoded,
+/// or if leveldb fails while iterating.
+pub struct TypedIterator<'a, K: Serializable + 'a, V, C: ValueCodec<V>, I = Iterator<'a, K>> {
+    inner: I,
+    done: bool,
+    marker: PhantomData<(&'a K, V, C)>,
+}
+
+impl<'a, K: Serializable + 'a, V, C: ValueCodec<V>> TypedDatabase<'a, K, V, C> {
+    /// Create a typed view over `database`.
+    pub fn new(database: &'a Database<K>) -> TypedDatabase<'a, K, V, C> {
+        TypedDatabase {
+            database: database,
+            marker: PhantomData,
+        }
+    }
+
+    /// The database this view reads from and writes to.
+    pub fn database(&self) -> &'a Database<K> {
+        self.database
+    }
+
+    /// get a value from the database.
+    pub fn get<BK: Borrow<K>>(
+        &self,
+        options: ReadOptions<'a, K>,
+        key: BK,
+    ) -> Result<Option<V>, Error> {
+        match self.database.get_bytes(options, key)? {
+            Some(bytes) => C::decode(&bytes).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// put a value into the database.
+    ///
+    /// If the key is already present in the database, it will be overwritten.
+    pub fn put<BK: Borrow<K>>(
+        &self,
+        options: WriteOptions,
+        key: BK,
+        value: &V,
+    ) -> Result<(), Error> {
+        let bytes = C::encode(value)?;
+        self.database.put(options, key, &bytes)
+    }
+
+    /// delete a value from the database.
+    pub fn delete<BK: Borrow<K>>(&self, options: WriteOptions, key: BK) -> Result<(), Error> {
+        self.database.delete(options, key)
+    }
+
+    /// Return an Iterator iterating over the decoded (Key,Value) pairs
+    pub fn iter(&self, options: ReadOptions<'a, K>) -> TypedIterator<'a, K, V, C> {
+        TypedIterator::new(self.database.iter(options))
+    }
+
+    /// Return an Iterator iterating over the decoded (Key,Value) pairs
+    /// within `range`.
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        options: ReadOptions<'a, K>,
+        range: R,
+    ) -> TypedIterator<'a, K, V, C> {
+        TypedIterator::new(self.database.range(options, range))
+    }
+}
+
+impl<'a, K, V, C, I> TypedIterator<'a, K, V, C, I>
+where
+    K: Serializable + 'a,
+    C: ValueCodec<V>,
+    I: LevelDBIterator<'a, K>,
+{
+    fn new(inner: I) -> TypedIterator<'a, K, V, C, I> {
+        TypedIterator {
+            inner: inner,
+            done: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Browse the keys backwards.
+    pub fn reverse(self) -> TypedIterator<'a, K, V, C, I::RevIter> {
+        TypedIterator::new(self.inner.reverse())
+    }
+}
+
+impl<'a, K, V, C, I> iter::Iterator for TypedIterator<'a, K, V, C, I>
+where
+    K: Serializable + 'a,
+    C: ValueCodec<V>,
+    I: LevelDBIterator<'a, K> + iter::Iterator<Item = (K, Vec<u8>)>,
+{
+    type Item = Result<(K, V), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let decoded = match self.inner.try_next() {
+            Ok(Some((key, value))) => C::decode(&value).map(|value| (key, value)),
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(e) => Err(e),
+        };
+        if decoded.is_err() {
+            self.done = true;
+        }
+        Some(decoded)
+    }
+}
@@ -48,6 +48,8 @@ extern crate libc;
 extern crate ffi_opaque;
 
 pub use crate::binding::{leveldb_major_version, leveldb_minor_version};
+#[cfg(feature = "tokio")]
+pub use crate::database::async_database;
 pub use crate::database::batch;
 pub use crate::database::compaction;
 pub use crate::database::comparator;
@@ -55,8 +57,13 @@ pub use crate::database::error;
 pub use crate::database::iterator;
 pub use crate::database::kv;
 pub use crate::database::management;
+pub use crate::database::namespace;
 pub use crate::database::options;
+pub use crate::database::properties;
+pub use crate::database::raw;
 pub use crate::database::snapshots;
+#[cfg(feature = "serde")]
+pub use crate::database::typed;
 
 mod binding;
 #[allow(missing_docs)]
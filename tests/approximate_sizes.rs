@@ -0,0 +1,25 @@
+use crate::utils::{db_put_simple, open_database, tmpdir};
+use leveldb::compaction::Compaction;
+
+#[test]
+fn test_approximate_sizes() {
+    let tmp = tmpdir("approximate_sizes");
+    let database = &mut open_database(tmp.path(), true);
+    let value = vec![7u8; 1024];
+    for i in 0..100 {
+        db_put_simple(database, i, &value);
+    }
+    database.compact(&0, &99);
+
+    let sizes = database.approximate_sizes(&[(0, 100), (200, 300)]);
+    assert_eq!(sizes.len(), 2);
+    assert!(sizes[0] > 0);
+    assert_eq!(sizes[1], 0);
+}
+
+#[test]
+fn test_approximate_sizes_empty_ranges() {
+    let tmp = tmpdir("approximate_sizes_empty");
+    let database = open_database::<i32>(tmp.path(), true);
+    assert!(database.approximate_sizes(&[]).is_empty());
+}
@@ -0,0 +1,59 @@
+use crate::utils::{open_database, tmpdir};
+use futures_core::Stream;
+use leveldb::async_database::{AsyncDatabase, RangeStream};
+use leveldb::batch::Writebatch;
+use leveldb::error::Error;
+use leveldb::options::{ReadOptions, WriteOptions};
+use std::future::poll_fn;
+use std::pin::Pin;
+
+async fn collect(mut stream: RangeStream<i32>) -> Vec<Result<(i32, Vec<u8>), Error>> {
+    let mut entries = vec![];
+    while let Some(entry) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+        entries.push(entry);
+    }
+    entries
+}
+
+#[tokio::test]
+async fn test_async_kv() {
+    let tmp = tmpdir("async_kv");
+    let database = AsyncDatabase::new(open_database(tmp.path(), true));
+
+    database.put(WriteOptions::new(), 1, &[1]).await.unwrap();
+    assert_eq!(
+        database.get(ReadOptions::new(), 1).await.unwrap(),
+        Some(vec![1])
+    );
+    database.delete(WriteOptions::new(), 1).await.unwrap();
+    assert_eq!(database.get(ReadOptions::new(), 1).await.unwrap(), None);
+
+    let mut batch = Writebatch::new();
+    batch.put(2, &[2]);
+    batch.put(3, &[3]);
+    database.write(WriteOptions::new(), batch).await.unwrap();
+    database.compact(None, Some(3)).await.unwrap();
+    assert_eq!(
+        database.get(ReadOptions::new(), 3).await.unwrap(),
+        Some(vec![3])
+    );
+}
+
+#[tokio::test]
+async fn test_async_range_stream() {
+    let tmp = tmpdir("async_range_stream");
+    let database = AsyncDatabase::new(open_database(tmp.path(), true));
+    for key in 0..10 {
+        database
+            .put(WriteOptions::new(), key, &[key as u8])
+            .await
+            .unwrap();
+    }
+
+    let entries = collect(database.range_stream(ReadOptions::new(), 2..8, 4)).await;
+    let keys: Vec<i32> = entries.into_iter().map(|entry| entry.unwrap().0).collect();
+    assert_eq!(keys, vec![2, 3, 4, 5, 6, 7]);
+
+    let entries = collect(database.range_stream(ReadOptions::new(), .., 5)).await;
+    assert_eq!(entries.len(), 10);
+}
@@ -2,6 +2,8 @@
 mod compaction {
     use crate::utils::{db_put_simple, open_database, tmpdir};
     use leveldb::compaction::Compaction;
+    use leveldb::kv::KV;
+    use leveldb::options::ReadOptions;
 
     #[test]
     fn test_iterator_from_to() {
@@ -17,4 +19,35 @@ mod compaction {
         let to = 4;
         database.compact(&from, &to);
     }
+
+    #[test]
+    fn test_compact_open_ended() {
+        let tmp = tmpdir("compact_open_ended");
+        let database = &mut open_database(tmp.path(), true);
+        db_put_simple(database, 1, &[1]);
+        db_put_simple(database, 2, &[2]);
+        db_put_simple(database, 3, &[3]);
+
+        let from = 2;
+        database.compact_range(Some(&from), None);
+        database.compact_range(None, Some(&from));
+
+        for i in 1..4 {
+            let read_opts = ReadOptions::new();
+            assert_eq!(database.get(read_opts, i).unwrap(), Some(vec![i as u8]));
+        }
+    }
+
+    #[test]
+    fn test_compact_all() {
+        let tmp = tmpdir("compact_all");
+        let database = &mut open_database(tmp.path(), true);
+        db_put_simple(database, 1, &[1]);
+        db_put_simple(database, 2, &[2]);
+
+        database.compact_all();
+
+        let read_opts = ReadOptions::new();
+        assert_eq!(database.get(read_opts, 2).unwrap(), Some(vec![2]));
+    }
 }
@@ -1,24 +1,29 @@
 #[cfg(test)]
 mod comparator {
     use crate::utils::{db_put_simple, tmpdir};
-    use leveldb::comparator::{Comparator, OrdComparator};
+    use leveldb::batch::{Batch, Writebatch};
+    use leveldb::comparator::{
+        Comparator, NumericSuffixComparator, OrdComparator, ReverseBytewiseComparator,
+    };
     use leveldb::database::serializable::Serializable;
     use leveldb::database::Database;
-    use leveldb::iterator::Iterable;
-    use leveldb::options::{Options, ReadOptions};
-    use libc::c_char;
+    use leveldb::error::ErrorKind;
+    use leveldb::iterator::{Iterable, LevelDBIterator};
+    use leveldb::kv::KV;
+    use leveldb::options::{Options, ReadOptions, WriteOptions};
     use std::cmp::Ordering;
+    use std::ffi::CStr;
     use std::marker::PhantomData;
 
     struct ReverseComparator<K> {
         marker: PhantomData<K>,
     }
 
-    impl<K: Serializable + Ord> Comparator for ReverseComparator<K> {
+    impl<K: Serializable + Ord + Send + Sync> Comparator for ReverseComparator<K> {
         type K = K;
 
-        fn name(&self) -> *const c_char {
-            "reverse".as_ptr() as *const c_char
+        fn name(&self) -> &CStr {
+            c"reverse"
         }
 
         fn compare(&self, a: &K, b: &K) -> Ordering {
@@ -61,4 +66,214 @@ mod comparator {
         assert_eq!((1, vec![1]), iter.next().unwrap());
         assert_eq!((2, vec![2]), iter.next().unwrap());
     }
+
+    #[test]
+    fn test_comparator_malformed_keys_sort_last() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("malformed_comparator");
+        let comparator: OrdComparator<Vec<u8>> = OrdComparator::new("foo");
+        let database = Database::open_with_comparator(tmp.path(), opts, comparator).unwrap();
+        db_put_simple(&database, vec![0], &[0]);
+        drop(database);
+
+        let comparator: OrdComparator<i32> = OrdComparator::new("foo");
+        let database =
+            Database::open_with_comparator(tmp.path(), Options::new(), comparator).unwrap();
+        db_put_simple(&database, 2, &[2]);
+        db_put_simple(&database, 1, &[1]);
+
+        let read_opts = ReadOptions::new();
+        let mut iter = database.keys_iter(read_opts).fallible();
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert_eq!(iter.next().unwrap().unwrap(), 2);
+        assert!(iter.next().unwrap().is_err());
+    }
+
+    struct PanickingComparator;
+
+    impl Comparator for PanickingComparator {
+        type K = i32;
+
+        fn name(&self) -> &CStr {
+            c"panicking"
+        }
+
+        fn compare(&self, a: &i32, b: &i32) -> Ordering {
+            if *a == 13 || *b == 13 {
+                panic!("unlucky key");
+            }
+            a.cmp(b)
+        }
+    }
+
+    #[test]
+    fn test_panicking_comparator_poisons_database() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("panicking_comparator");
+        let database =
+            Database::open_with_comparator(tmp.path(), opts, PanickingComparator).unwrap();
+        db_put_simple(&database, 1, &[1]);
+        assert!(!database.is_poisoned());
+
+        let res = database.put(WriteOptions::new(), 13, &[13]);
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Poisoned);
+        assert!(database.is_poisoned());
+
+        let res = database.put(WriteOptions::new(), 2, &[2]);
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Poisoned);
+        let res = database.get(ReadOptions::new(), 1);
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Poisoned);
+        let mut iter = database.keys_iter(ReadOptions::new());
+        assert_eq!(iter.try_next().unwrap_err().kind(), ErrorKind::Poisoned);
+    }
+
+    #[test]
+    fn test_panicking_comparator_fails_triggering_writes() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("panicking_comparator_batch");
+        let database =
+            Database::open_with_comparator(tmp.path(), opts, PanickingComparator).unwrap();
+        db_put_simple(&database, 1, &[1]);
+        let mut batch = Writebatch::new();
+        batch.put(13, &[13]);
+        let res = database.write(WriteOptions::new(), &batch);
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Poisoned);
+
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("panicking_comparator_delete");
+        let database =
+            Database::open_with_comparator(tmp.path(), opts, PanickingComparator).unwrap();
+        db_put_simple(&database, 1, &[1]);
+        let res = database.delete(WriteOptions::new(), 13);
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::Poisoned);
+    }
+
+    #[test]
+    fn test_comparator_mismatch() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("comparator_mismatch");
+        let comparator: OrdComparator<i32> = OrdComparator::new("foo");
+        let database = Database::open_with_comparator(tmp.path(), opts, comparator).unwrap();
+        drop(database);
+
+        let comparator: OrdComparator<i32> = OrdComparator::new("bar");
+        let res = Database::open_with_comparator(tmp.path(), Options::new(), comparator);
+        let err = res.err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::ComparatorMismatch);
+        assert_eq!(err.comparator_mismatch(), Some(("foo", "bar")));
+    }
+
+    #[test]
+    fn test_reverse_bytewise_comparator() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("reverse_bytewise_comparator");
+        let database =
+            &mut Database::open_with_raw_comparator(tmp.path(), opts, ReverseBytewiseComparator)
+                .unwrap();
+        db_put_simple(database, 1, &[1]);
+        db_put_simple(database, 2, &[2]);
+        db_put_simple(database, -1, &[3]);
+
+        let read_opts = ReadOptions::new();
+        let keys: Vec<i32> = database.keys_iter(read_opts).collect();
+        assert_eq!(keys, vec![2, 1, -1]);
+    }
+
+    #[test]
+    fn test_numeric_suffix_comparator() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("numeric_suffix_comparator");
+        let database =
+            &mut Database::open_with_raw_comparator(tmp.path(), opts, NumericSuffixComparator)
+                .unwrap();
+        for key in ["file10", "file2", "file", "file02", "log1", "file9"] {
+            db_put_simple(database, key.to_string(), &[1]);
+        }
+
+        let read_opts = ReadOptions::new();
+        let keys: Vec<String> = database.keys_iter(read_opts).collect();
+        assert_eq!(
+            keys,
+            vec!["file", "file02", "file2", "file9", "file10", "log1"]
+        );
+    }
+
+    #[test]
+    fn test_compare_fn() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("compare_fn");
+        let database = &mut Database::open_with_compare_fn(
+            tmp.path(),
+            opts,
+            "descending",
+            |a: &i32, b: &i32| b.cmp(a),
+        )
+        .unwrap();
+        db_put_simple(database, 1, &[1]);
+        db_put_simple(database, 3, &[3]);
+        db_put_simple(database, 2, &[2]);
+
+        let read_opts = ReadOptions::new();
+        let keys: Vec<i32> = database.keys_iter(read_opts).collect();
+        assert_eq!(keys, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_raw_compare_fn() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("raw_compare_fn");
+        let database = &mut Database::open_with_raw_compare_fn(
+            tmp.path(),
+            opts,
+            "by_length",
+            |a: &[u8], b: &[u8]| a.len().cmp(&b.len()).then(a.cmp(b)),
+        )
+        .unwrap();
+        for key in ["ccc", "a", "bb", "b"] {
+            db_put_simple(database, key.to_string(), &[1]);
+        }
+
+        let read_opts = ReadOptions::new();
+        let keys: Vec<String> = database.keys_iter(read_opts).collect();
+        assert_eq!(keys, vec!["a", "b", "bb", "ccc"]);
+    }
+
+    #[test]
+    fn test_compare_fn_invalid_name() {
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("compare_fn_invalid_name");
+        let res =
+            Database::open_with_compare_fn(tmp.path(), opts, "a\0b", |a: &i32, b: &i32| a.cmp(b));
+        assert_eq!(res.err().unwrap().kind(), ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_comparator_iterator_bounds() {
+        let comparator: ReverseComparator<i32> = ReverseComparator {
+            marker: PhantomData,
+        };
+        let mut opts = Options::new();
+        opts.create_if_missing = true;
+        let tmp = tmpdir("comparator_bounds");
+        let database = &mut Database::open_with_comparator(tmp.path(), opts, comparator).unwrap();
+        for key in 1..6 {
+            db_put_simple(database, key, &[key as u8]);
+        }
+
+        let from = 4;
+        let to = 2;
+        let read_opts = ReadOptions::new();
+        let keys: Vec<i32> = database.keys_iter(read_opts).from(&from).to(&to).collect();
+        assert_eq!(keys, vec![4, 3]);
+    }
 }
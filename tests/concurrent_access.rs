@@ -1,6 +1,8 @@
 use crate::utils::{open_database, tmpdir};
 use leveldb::database::kv::KV;
-use leveldb::options::{Options, WriteOptions};
+use leveldb::iterator::Iterable;
+use leveldb::options::{Options, ReadOptions, WriteOptions};
+use leveldb::snapshots::Snapshots;
 
 #[test]
 fn access_from_threads() {
@@ -31,3 +33,26 @@ fn access_from_threads() {
         .map(JoinHandle::join)
         .collect::<Vec<_>>();
 }
+
+#[test]
+fn iterator_to_thread() {
+    use std::thread;
+
+    let tmp = tmpdir("iterator_to_thread");
+    let database = open_database(tmp.path(), true);
+    database.put(WriteOptions::new(), 1, &[1]).unwrap();
+    database.put(WriteOptions::new(), 2, &[2]).unwrap();
+
+    let snapshot = database.snapshot();
+    database.put(WriteOptions::new(), 3, &[3]).unwrap();
+    thread::scope(|scope| {
+        let iter = database.keys_iter(ReadOptions::new());
+        let keys = scope.spawn(move || iter.collect::<Vec<i32>>());
+        let snapshot_keys =
+            scope.spawn(|| snapshot.keys_iter(ReadOptions::new()).collect::<Vec<i32>>());
+        let snapshot_value = scope.spawn(|| snapshot.get(ReadOptions::new(), 3).unwrap());
+        assert_eq!(keys.join().unwrap(), vec![1, 2, 3]);
+        assert_eq!(snapshot_keys.join().unwrap(), vec![1, 2]);
+        assert_eq!(snapshot_value.join().unwrap(), None);
+    });
+}
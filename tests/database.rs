@@ -1,6 +1,8 @@
 use crate::utils::tmpdir;
 use leveldb::database::Database;
+use leveldb::error::ErrorKind;
 use leveldb::options::Options;
+use std::path::Path;
 
 #[test]
 fn test_create_options() {
@@ -24,3 +26,51 @@ fn test_open_non_existant_database_without_create() {
     let res: Result<Database<i32>, _> = Database::open(tmp.path(), opts);
     assert!(res.is_err());
 }
+
+#[test]
+fn test_open_non_existant_database_error_kind() {
+    let mut opts = Options::new();
+    opts.create_if_missing = false;
+    let tmp = tmpdir("missing_kind");
+    let res: Result<Database<i32>, _> = Database::open(tmp.path(), opts);
+    assert_eq!(res.err().unwrap().kind(), ErrorKind::InvalidArgument);
+}
+
+#[test]
+fn test_open_database_twice_error_kind() {
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let tmp = tmpdir("locked");
+    let _db: Database<i32> = Database::open(tmp.path(), opts).unwrap();
+    let res: Result<Database<i32>, _> = Database::open(tmp.path(), Options::new());
+    assert_eq!(res.err().unwrap().kind(), ErrorKind::Lock);
+}
+
+#[test]
+fn test_open_database_with_invalid_path() {
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let res: Result<Database<i32>, _> = Database::open(Path::new("invalid\0path"), opts);
+    assert_eq!(res.err().unwrap().kind(), ErrorKind::InvalidArgument);
+}
+
+#[test]
+fn test_error_kind_from_message() {
+    assert_eq!(
+        ErrorKind::from_message("NotFound: missing"),
+        ErrorKind::NotFound
+    );
+    assert_eq!(
+        ErrorKind::from_message("Corruption: bad block"),
+        ErrorKind::Corruption
+    );
+    assert_eq!(
+        ErrorKind::from_message("Not implemented: nope"),
+        ErrorKind::NotSupported
+    );
+    assert_eq!(
+        ErrorKind::from_message("IO error: disk full"),
+        ErrorKind::IOError
+    );
+    assert_eq!(ErrorKind::from_message("something else"), ErrorKind::Other);
+}
@@ -0,0 +1,171 @@
+use crate::utils::{db_put_simple, open_database, tmpdir};
+use leveldb::database::serializable::Serializable;
+use leveldb::error::ErrorKind;
+use leveldb::iterator::Iterable;
+use leveldb::options::ReadOptions;
+
+#[derive(Serializable, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Event {
+    user: String,
+    day: u32,
+    seq: i64,
+}
+
+#[derive(Serializable, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Score(u16, #[serializable(descending)] i32, String);
+
+#[derive(Serializable, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Compact {
+    #[serializable(width = 3)]
+    small: i32,
+    #[serializable(width = 5)]
+    large: u64,
+}
+
+#[derive(Serializable, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Entity {
+    Unit,
+    User { id: u32 },
+    Group(String, u8),
+}
+
+fn assert_order<K: Serializable + Ord + Clone + std::fmt::Debug>(values: &[K]) {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let mut by_encoding = values.to_vec();
+    by_encoding.sort_by_key(|value| value.as_u8());
+    assert_eq!(by_encoding, sorted);
+    for value in values {
+        assert_eq!(&K::try_from_u8(&value.as_u8()).unwrap(), value);
+    }
+}
+
+#[test]
+fn test_derive_struct_order() {
+    let mut events = vec![];
+    for user in ["", "a", "a\0", "ab", "b"] {
+        for day in [0, 1, 256] {
+            for seq in [i64::MIN, -1, 0, 7] {
+                events.push(Event {
+                    user: user.to_string(),
+                    day: day,
+                    seq: seq,
+                });
+            }
+        }
+    }
+    assert_order(&events);
+}
+
+#[test]
+fn test_derive_descending() {
+    let mut scores = vec![];
+    for a in [0, 9] {
+        for b in [i32::MIN, -3, 0, 12] {
+            for c in ["", "x", "xy"] {
+                scores.push(Score(a, b, c.to_string()));
+            }
+        }
+    }
+    let mut expected = scores.clone();
+    expected.sort_by(|x, y| (x.0, y.1, &x.2).cmp(&(y.0, x.1, &y.2)));
+    scores.sort_by_key(|score| score.as_u8());
+    assert_eq!(scores, expected);
+    for score in &scores {
+        assert_eq!(&Score::try_from_u8(&score.as_u8()).unwrap(), score);
+    }
+}
+
+#[test]
+fn test_derive_width() {
+    let value = Compact {
+        small: -1,
+        large: 1 << 32,
+    };
+    assert_eq!(value.as_u8().len(), 8);
+    assert_order(&[
+        Compact {
+            small: -(1 << 23),
+            large: 0,
+        },
+        Compact {
+            small: -1,
+            large: (1 << 40) - 1,
+        },
+        Compact { small: 0, large: 1 },
+        Compact {
+            small: (1 << 23) - 1,
+            large: 0,
+        },
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "doesn't fit in 3 bytes")]
+fn test_derive_width_overflow() {
+    Compact {
+        small: 1 << 23,
+        large: 0,
+    }
+    .as_u8();
+}
+
+#[test]
+fn test_derive_enum() {
+    assert_order(&[
+        Entity::Unit,
+        Entity::User { id: 0 },
+        Entity::User { id: u32::MAX },
+        Entity::Group("".to_string(), 3),
+        Entity::Group("admins".to_string(), 1),
+    ]);
+    assert_eq!(Entity::Unit.as_u8(), vec![0]);
+}
+
+#[test]
+fn test_derive_malformed() {
+    let mut key = Entity::User { id: 1 }.as_u8();
+    assert_eq!(
+        Entity::try_from_u8(&key[..3]).unwrap_err().kind(),
+        ErrorKind::Corruption
+    );
+    key.push(0);
+    assert_eq!(
+        Entity::try_from_u8(&key).unwrap_err().kind(),
+        ErrorKind::Corruption
+    );
+    assert_eq!(
+        Entity::try_from_u8(&[3]).unwrap_err().kind(),
+        ErrorKind::Corruption
+    );
+}
+
+#[test]
+fn test_derive_database_order() {
+    let tmp = tmpdir("derive_order");
+    let database = open_database(tmp.path(), true);
+    let events = [
+        Event {
+            user: "bob".to_string(),
+            day: 2,
+            seq: -5,
+        },
+        Event {
+            user: "alice".to_string(),
+            day: 10,
+            seq: 0,
+        },
+        Event {
+            user: "alice".to_string(),
+            day: 2,
+            seq: 3,
+        },
+    ];
+    for event in events.iter() {
+        db_put_simple(&database, event.clone(), &[1]);
+    }
+    let keys: Vec<Event> = database.keys_iter(ReadOptions::new()).collect();
+    let mut expected = events.to_vec();
+    expected.sort();
+    assert_eq!(keys, expected);
+}
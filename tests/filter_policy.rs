@@ -0,0 +1,93 @@
+use crate::utils::tmpdir;
+use leveldb::compaction::Compaction;
+use leveldb::database::filter_policy::{CustomFilterPolicy, FilterPolicy};
+use leveldb::database::kv::KV;
+use leveldb::database::Database;
+use leveldb::options::{Options, ReadOptions, WriteOptions};
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_open_database_with_bloom_filter() {
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    opts.filter_policy = Some(FilterPolicy::new_bloom(10));
+    let tmp = tmpdir("bloom_filter");
+    let database: Database<i32> = Database::open(tmp.path(), opts).unwrap();
+    database.put(WriteOptions::new(), 1, &[1]).unwrap();
+
+    assert_eq!(database.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+    assert_eq!(database.get(ReadOptions::new(), 2).unwrap(), None);
+}
+
+struct LastByteFilter {
+    created: Arc<AtomicUsize>,
+}
+
+impl CustomFilterPolicy for LastByteFilter {
+    fn name(&self) -> &CStr {
+        CStr::from_bytes_with_nul(b"last_byte\0").unwrap()
+    }
+
+    fn create_filter(&self, keys: &[&[u8]]) -> Vec<u8> {
+        self.created.fetch_add(1, Ordering::SeqCst);
+        keys.iter().filter_map(|k| k.last().copied()).collect()
+    }
+
+    fn key_may_match(&self, key: &[u8], filter: &[u8]) -> bool {
+        match key.last() {
+            Some(b) => filter.contains(b),
+            None => true,
+        }
+    }
+}
+
+#[test]
+fn test_open_database_with_custom_filter() {
+    let created = Arc::new(AtomicUsize::new(0));
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    opts.filter_policy = Some(FilterPolicy::new_custom(LastByteFilter {
+        created: created.clone(),
+    }));
+    let tmp = tmpdir("custom_filter");
+    let database: Database<i32> = Database::open(tmp.path(), opts).unwrap();
+    database.put(WriteOptions::new(), 1, &[1]).unwrap();
+    database.put(WriteOptions::new(), 3, &[3]).unwrap();
+    database.compact(&1, &3);
+
+    assert!(created.load(Ordering::SeqCst) > 0);
+    assert_eq!(database.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+    assert_eq!(database.get(ReadOptions::new(), 2).unwrap(), None);
+    assert_eq!(database.get(ReadOptions::new(), 3).unwrap(), Some(vec![3]));
+}
+
+struct PanickingFilter;
+
+impl CustomFilterPolicy for PanickingFilter {
+    fn name(&self) -> &CStr {
+        CStr::from_bytes_with_nul(b"panicking\0").unwrap()
+    }
+
+    fn create_filter(&self, _keys: &[&[u8]]) -> Vec<u8> {
+        panic!("create_filter");
+    }
+
+    fn key_may_match(&self, _key: &[u8], _filter: &[u8]) -> bool {
+        panic!("key_may_match");
+    }
+}
+
+#[test]
+fn test_panicking_custom_filter_matches_all_keys() {
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    opts.filter_policy = Some(FilterPolicy::new_custom(PanickingFilter));
+    let tmp = tmpdir("panicking_filter");
+    let database: Database<i32> = Database::open(tmp.path(), opts).unwrap();
+    database.put(WriteOptions::new(), 1, &[1]).unwrap();
+    database.compact(&1, &1);
+
+    assert_eq!(database.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+}
@@ -1,7 +1,17 @@
 use crate::utils::{db_put_simple, open_database, tmpdir};
+use leveldb::compaction::Compaction;
+use leveldb::comparator::{NumericSuffixComparator, ReverseBytewiseComparator};
+use leveldb::database::serializable::Serializable;
+use leveldb::database::Database;
+use leveldb::error::ErrorKind;
+use leveldb::iterator::ArcIterator;
 use leveldb::iterator::Iterable;
 use leveldb::iterator::LevelDBIterator;
-use leveldb::options::ReadOptions;
+use leveldb::options::{Options, ReadOptions};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::sync::Arc;
 
 #[test]
 fn test_iterator() {
@@ -198,7 +208,7 @@ fn test_iterator_bounded_next_6() {
     while let Some((k, _)) = iter.next() {
         keys.push(k)
     }
-    assert_eq!(keys, vec![]);
+    assert_eq!(keys, Vec::<i32>::new());
 }
 
 #[test]
@@ -216,7 +226,7 @@ fn test_iterator_bounded_next_7() {
     while let Some((k, _)) = iter.next() {
         keys.push(k)
     }
-    assert_eq!(keys, vec![]);
+    assert_eq!(keys, Vec::<i32>::new());
 }
 
 #[test]
@@ -276,3 +286,328 @@ fn test_seek_after_inserted() {
     iter.seek(&3);
     assert!(!iter.valid())
 }
+
+#[test]
+fn test_iterator_try_next() {
+    let tmp = tmpdir("try_next");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, 1, &[1]);
+
+    let read_opts = ReadOptions::new();
+    let mut iter = database.iter(read_opts);
+
+    assert_eq!(iter.try_next().unwrap(), Some((1, vec![1])));
+    assert_eq!(iter.try_next().unwrap(), None);
+    assert!(iter.status().is_ok());
+}
+
+#[test]
+fn test_fallible_iterator() {
+    let tmp = tmpdir("fallible");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, 1, &[1]);
+    db_put_simple(database, 2, &[2]);
+
+    let read_opts = ReadOptions::new();
+    let keys: Result<Vec<i32>, _> = database.keys_iter(read_opts).fallible().collect();
+    assert_eq!(keys.unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn test_fallible_iterator_malformed_key() {
+    let tmp = tmpdir("malformed_key");
+    let database = open_database::<Vec<u8>>(tmp.path(), true);
+    db_put_simple(&database, vec![0x80, 0, 0, 1], &[1]);
+    db_put_simple(&database, vec![0x80, 0, 1], &[2]);
+    drop(database);
+
+    let database = open_database::<i32>(tmp.path(), false);
+    let read_opts = ReadOptions::new();
+    let mut iter = database.keys_iter(read_opts).fallible();
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert_eq!(
+        iter.next().unwrap().unwrap_err().kind(),
+        ErrorKind::Corruption
+    );
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_fallible_reverse_values_malformed_key() {
+    let tmp = tmpdir("reverse_values_malformed_key");
+    let database = open_database::<Vec<u8>>(tmp.path(), true);
+    db_put_simple(&database, vec![0x80, 0, 0, 1], &[1]);
+    db_put_simple(&database, vec![0x80, 0, 1], &[2]);
+    drop(database);
+
+    // values are yielded without decoding the keys
+    let database = open_database::<i32>(tmp.path(), false);
+    let read_opts = ReadOptions::new();
+    let values: Result<Vec<Vec<u8>>, _> = database
+        .value_iter(read_opts)
+        .reverse()
+        .fallible()
+        .collect();
+    assert_eq!(values.unwrap(), vec![vec![2], vec![1]]);
+}
+
+#[test]
+fn test_fallible_iterator_corrupted_table() {
+    let tmp = tmpdir("corrupted_table");
+    let database = open_database(tmp.path(), true);
+    for key in 0..100 {
+        db_put_simple(&database, key, &[key as u8; 16]);
+    }
+    database.compact_all();
+    drop(database);
+
+    // overwrite the start of the first data block of every table
+    for entry in fs::read_dir(tmp.path()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension() == Some("ldb".as_ref()) {
+            let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+            file.write_all(&[0xff; 16]).unwrap();
+        }
+    }
+
+    let database = open_database::<i32>(tmp.path(), false);
+    let mut read_opts = ReadOptions::new();
+    read_opts.verify_checksums = true;
+    let error = database
+        .keys_iter(read_opts)
+        .fallible()
+        .find_map(|key| key.err())
+        .unwrap();
+    assert_eq!(error.kind(), ErrorKind::Corruption);
+}
+
+#[test]
+fn test_range() {
+    let tmp = tmpdir("range");
+    let database = &mut open_database(tmp.path(), true);
+    for key in 1..6 {
+        db_put_simple(database, key, &[key as u8]);
+    }
+
+    let keys = |range: (Bound<i32>, Bound<i32>)| -> Vec<i32> {
+        database.range_keys(ReadOptions::new(), range).collect()
+    };
+    assert_eq!(keys((Unbounded, Unbounded)), vec![1, 2, 3, 4, 5]);
+    assert_eq!(keys((Included(2), Excluded(4))), vec![2, 3]);
+    assert_eq!(keys((Included(2), Included(4))), vec![2, 3, 4]);
+    assert_eq!(keys((Excluded(2), Unbounded)), vec![3, 4, 5]);
+    assert_eq!(keys((Unbounded, Included(3))), vec![1, 2, 3]);
+    assert_eq!(keys((Included(7), Unbounded)), Vec::<i32>::new());
+
+    let entries: Vec<(i32, Vec<u8>)> = database.range(ReadOptions::new(), 2..=3).collect();
+    assert_eq!(entries, vec![(2, vec![2]), (3, vec![3])]);
+    let values: Vec<Vec<u8>> = database.range_values(ReadOptions::new(), ..2).collect();
+    assert_eq!(values, vec![vec![1]]);
+}
+
+#[test]
+fn test_range_reverse() {
+    let tmp = tmpdir("range_reverse");
+    let database = &mut open_database(tmp.path(), true);
+    for key in [1, 2, 4, 5] {
+        db_put_simple(database, key, &[key as u8]);
+    }
+
+    let keys = |range: (Bound<i32>, Bound<i32>)| -> Vec<i32> {
+        database
+            .range_keys(ReadOptions::new(), range)
+            .reverse()
+            .collect()
+    };
+    assert_eq!(keys((Unbounded, Unbounded)), vec![5, 4, 2, 1]);
+    assert_eq!(keys((Included(2), Excluded(4))), vec![2]);
+    assert_eq!(keys((Included(2), Included(4))), vec![4, 2]);
+    assert_eq!(keys((Excluded(2), Included(3))), Vec::<i32>::new());
+    assert_eq!(keys((Unbounded, Excluded(3))), vec![2, 1]);
+    assert_eq!(keys((Unbounded, Included(9))), vec![5, 4, 2, 1]);
+    assert_eq!(keys((Excluded(1), Unbounded)), vec![5, 4, 2]);
+
+    let last = database.range(ReadOptions::new(), ..5).last();
+    assert_eq!(last, Some((4, vec![4])));
+
+    let values: Vec<Vec<u8>> = database
+        .range_values(ReadOptions::new(), 2..)
+        .reverse()
+        .collect();
+    assert_eq!(values, vec![vec![5], vec![4], vec![2]]);
+}
+
+#[test]
+fn test_prefix_iter() {
+    let tmp = tmpdir("prefix_iter");
+    let database = &mut open_database::<Vec<u8>>(tmp.path(), true);
+    for key in [&b"a"[..], b"ab", b"abc", b"abd", b"ac", b"b"] {
+        db_put_simple(database, key.to_vec(), key);
+    }
+
+    let keys: Vec<Vec<u8>> = database.prefix_keys(ReadOptions::new(), b"ab").collect();
+    assert_eq!(keys, vec![b"ab".to_vec(), b"abc".to_vec(), b"abd".to_vec()]);
+    let keys: Vec<Vec<u8>> = database
+        .prefix_keys(ReadOptions::new(), b"ab")
+        .reverse()
+        .collect();
+    assert_eq!(keys, vec![b"abd".to_vec(), b"abc".to_vec(), b"ab".to_vec()]);
+    let values: Vec<Vec<u8>> = database.prefix_values(ReadOptions::new(), b"a").collect();
+    assert_eq!(values.len(), 5);
+    let values: Vec<Vec<u8>> = database
+        .prefix_values(ReadOptions::new(), b"ab")
+        .reverse()
+        .collect();
+    assert_eq!(
+        values,
+        vec![b"abd".to_vec(), b"abc".to_vec(), b"ab".to_vec()]
+    );
+    let entries: Vec<(Vec<u8>, Vec<u8>)> = database
+        .prefix_iter(ReadOptions::new(), b"ab")
+        .strip_prefix()
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (b"".to_vec(), b"ab".to_vec()),
+            (b"c".to_vec(), b"abc".to_vec()),
+            (b"d".to_vec(), b"abd".to_vec())
+        ]
+    );
+    assert_eq!(database.prefix_keys(ReadOptions::new(), b"x").next(), None);
+}
+
+#[test]
+fn test_prefix_iter_trailing_ff() {
+    let tmp = tmpdir("prefix_iter_ff");
+    let database = &mut open_database::<Vec<u8>>(tmp.path(), true);
+    for key in [
+        vec![0x01],
+        vec![0x01, 0xFF],
+        vec![0x01, 0xFF, 0x02],
+        vec![0x02],
+    ] {
+        db_put_simple(database, key, &[1]);
+    }
+    db_put_simple(database, vec![0xFF, 0xFF], &[1]);
+    db_put_simple(database, vec![0xFF, 0xFF, 0x00], &[1]);
+
+    let suffixes: Vec<Vec<u8>> = database
+        .prefix_keys(ReadOptions::new(), &[0x01, 0xFF])
+        .strip_prefix()
+        .collect();
+    assert_eq!(suffixes, vec![vec![], vec![0x02]]);
+    let keys: Vec<Vec<u8>> = database
+        .prefix_keys(ReadOptions::new(), &[0xFF, 0xFF])
+        .reverse()
+        .collect();
+    assert_eq!(keys, vec![vec![0xFF, 0xFF, 0x00], vec![0xFF, 0xFF]]);
+}
+
+#[test]
+fn test_prefix_iter_custom_comparator() {
+    let tmp = tmpdir("prefix_iter_reverse");
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let database: Database<Vec<u8>> =
+        Database::open_with_raw_comparator(tmp.path(), opts, ReverseBytewiseComparator).unwrap();
+    for key in [&b"a"[..], b"ab", b"abc", b"abd", b"ac", b"b"] {
+        db_put_simple(&database, key.to_vec(), key);
+    }
+    let keys: Vec<Vec<u8>> = database.prefix_keys(ReadOptions::new(), b"ab").collect();
+    assert_eq!(keys, vec![b"abd".to_vec(), b"abc".to_vec(), b"ab".to_vec()]);
+    let keys: Vec<Vec<u8>> = database
+        .prefix_keys(ReadOptions::new(), b"ab")
+        .reverse()
+        .collect();
+    assert_eq!(keys, vec![b"ab".to_vec(), b"abc".to_vec(), b"abd".to_vec()]);
+    assert_eq!(database.prefix_keys(ReadOptions::new(), b"aa").next(), None);
+
+    let tmp = tmpdir("prefix_iter_numeric");
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let database: Database<Vec<u8>> =
+        Database::open_with_raw_comparator(tmp.path(), opts, NumericSuffixComparator).unwrap();
+    for key in [
+        &b"ite5"[..],
+        b"item2",
+        b"item10",
+        b"itemz",
+        b"iten1",
+        b"x9",
+        b"x10",
+    ] {
+        db_put_simple(&database, key.to_vec(), key);
+    }
+    let keys: Vec<Vec<u8>> = database.prefix_keys(ReadOptions::new(), b"item").collect();
+    assert_eq!(
+        keys,
+        vec![b"item2".to_vec(), b"item10".to_vec(), b"itemz".to_vec()]
+    );
+    let keys: Vec<Vec<u8>> = database
+        .prefix_keys(ReadOptions::new(), b"item")
+        .reverse()
+        .collect();
+    assert_eq!(
+        keys,
+        vec![b"itemz".to_vec(), b"item10".to_vec(), b"item2".to_vec()]
+    );
+    // x10 sorts right after x9, but doesn't start with the prefix
+    let keys: Vec<Vec<u8>> = database.prefix_keys(ReadOptions::new(), b"x9").collect();
+    assert_eq!(keys, vec![b"x9".to_vec()]);
+}
+
+#[test]
+fn test_raw_cursor() {
+    let tmp = tmpdir("raw_cursor");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, 1, &[1]);
+    db_put_simple(database, 2, &[2]);
+    db_put_simple(database, 3, &[3]);
+
+    let read_opts = ReadOptions::new();
+    let mut cursor = database.cursor(read_opts);
+    let mut values = vec![];
+    while cursor.valid() {
+        values.extend_from_slice(cursor.value_bytes());
+        cursor.advance();
+    }
+    assert_eq!(values, vec![1, 2, 3]);
+    assert!(!cursor.advance());
+
+    cursor.seek(&2.as_u8());
+    assert_eq!(cursor.key_bytes(), &2.as_u8()[..]);
+    assert_eq!(cursor.key().unwrap(), 2);
+    assert!(cursor.retreat());
+    assert_eq!(cursor.value_bytes(), &[1]);
+    assert!(!cursor.retreat());
+
+    cursor.seek_to_last();
+    assert_eq!(cursor.key().unwrap(), 3);
+    assert!(cursor.status().is_ok());
+}
+
+fn open_arc_iterator(path: &std::path::Path) -> ArcIterator<i32> {
+    let database = open_database(path, true);
+    db_put_simple(&database, 1, &[1]);
+    db_put_simple(&database, 2, &[2]);
+    db_put_simple(&database, 3, &[3]);
+    ArcIterator::new(Arc::new(database), ReadOptions::new())
+}
+
+#[test]
+fn test_arc_iterator() {
+    let tmp = tmpdir("arc_iter");
+    let iter = open_arc_iterator(tmp.path());
+    let entries: Vec<(i32, Vec<u8>)> = iter.collect();
+    assert_eq!(entries, vec![(1, vec![1]), (2, vec![2]), (3, vec![3])]);
+
+    let database = Arc::new(open_database::<i32>(tmp.path(), false));
+    let keys: Vec<i32> = ArcIterator::range(database.clone(), ReadOptions::new(), 2..)
+        .keys()
+        .reverse()
+        .collect();
+    assert_eq!(keys, vec![3, 2]);
+    let mut values = ArcIterator::new(database, ReadOptions::new()).values();
+    assert_eq!(values.try_next().unwrap(), Some(vec![1]));
+}
@@ -0,0 +1,157 @@
+use crate::utils::{open_database, tmpdir};
+use leveldb::batch::{Batch, Writebatch};
+use leveldb::compaction::Compaction;
+use leveldb::database::Database;
+use leveldb::error::ErrorKind;
+use leveldb::iterator::{Iterable, LevelDBIterator};
+use leveldb::kv::KV;
+use leveldb::namespace::{self, Namespace};
+use leveldb::options::{Options, ReadOptions, WriteOptions};
+use leveldb::snapshots::Snapshots;
+
+#[test]
+fn test_namespace_kv() {
+    let tmp = tmpdir("namespace_kv");
+    let database: Database<i32> = open_database(tmp.path(), true);
+    let users: Namespace<i32> = Namespace::open(&database, "users").unwrap();
+    let groups: Namespace<i32> = Namespace::open(&database, "groups").unwrap();
+    users.put(WriteOptions::new(), 1, &[1]).unwrap();
+    groups.put(WriteOptions::new(), 1, &[2]).unwrap();
+
+    assert_eq!(users.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+    assert_eq!(groups.get(ReadOptions::new(), 1).unwrap(), Some(vec![2]));
+    assert_eq!(database.get(ReadOptions::new(), 1).unwrap(), None);
+    groups.delete(WriteOptions::new(), 1).unwrap();
+    assert_eq!(groups.get(ReadOptions::new(), 1).unwrap(), None);
+    assert_eq!(users.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+
+    // opening again returns the same namespace
+    let again: Namespace<i32> = Namespace::open(&database, "users").unwrap();
+    assert_eq!(again.id(), users.id());
+    let by_id: Namespace<i32> = Namespace::open_id(&database, users.id()).unwrap();
+    assert_eq!(by_id.name(), "users");
+    assert_eq!(
+        Namespace::<i32>::open_id(&database, 99)
+            .err()
+            .unwrap()
+            .kind(),
+        ErrorKind::NotFound
+    );
+}
+
+#[test]
+fn test_namespace_batch_and_iteration() {
+    let tmp = tmpdir("namespace_iter");
+    let database: Database<i32> = open_database(tmp.path(), true);
+    let first: Namespace<i32> = Namespace::open(&database, "first").unwrap();
+    let second: Namespace<i32> = Namespace::open(&database, "second").unwrap();
+    let third: Namespace<i32> = Namespace::open(&database, "third").unwrap();
+
+    let mut batch = Writebatch::new();
+    for i in 1..6 {
+        batch.put(i, &[i as u8]);
+    }
+    first.write(WriteOptions::new(), &batch).unwrap();
+    third.write(WriteOptions::new(), &batch).unwrap();
+    second.put(WriteOptions::new(), 3, &[3]).unwrap();
+
+    let keys: Vec<i32> = second.keys_iter(ReadOptions::new()).collect();
+    assert_eq!(keys, vec![3]);
+    let keys: Vec<i32> = first.keys_iter(ReadOptions::new()).reverse().collect();
+    assert_eq!(keys, vec![5, 4, 3, 2, 1]);
+    let keys: Vec<i32> = third.range_keys(ReadOptions::new(), 4..).collect();
+    assert_eq!(keys, vec![4, 5]);
+    let keys: Vec<i32> = first
+        .range_keys(ReadOptions::new(), ..3)
+        .reverse()
+        .collect();
+    assert_eq!(keys, vec![2, 1]);
+    let keys: Vec<i32> = first
+        .keys_iter(ReadOptions::new())
+        .from(&2)
+        .to(&4)
+        .collect();
+    assert_eq!(keys, vec![2, 3]);
+    assert_eq!(first.keys_iter(ReadOptions::new()).last(), Some(5));
+
+    let mut cursor = second.cursor(ReadOptions::new());
+    assert_eq!(cursor.key().unwrap(), 3);
+    assert!(!cursor.advance());
+    cursor.seek_to_last();
+    assert_eq!(cursor.key().unwrap(), 3);
+    assert!(!cursor.retreat());
+
+    let snapshot = first.snapshot();
+    first.delete(WriteOptions::new(), 1).unwrap();
+    assert_eq!(snapshot.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+    assert_eq!(snapshot.keys_iter(ReadOptions::new()).count(), 5);
+    assert_eq!(first.keys_iter(ReadOptions::new()).count(), 4);
+
+    first.compact_all();
+    assert_eq!(second.keys_iter(ReadOptions::new()).count(), 1);
+}
+
+#[test]
+fn test_namespace_management() {
+    let tmp = tmpdir("namespace_management");
+    let database: Database<i32> = open_database(tmp.path(), true);
+    let users: Namespace<i32> = Namespace::open(&database, "users").unwrap();
+    let groups: Namespace<i32> = Namespace::open(&database, "groups").unwrap();
+    users.put(WriteOptions::new(), 1, &[1]).unwrap();
+    groups.put(WriteOptions::new(), 1, &[2]).unwrap();
+    assert_eq!(
+        namespace::list(&database).unwrap(),
+        vec![
+            ("groups".to_string(), groups.id()),
+            ("users".to_string(), users.id())
+        ]
+    );
+
+    namespace::rename(&database, "users", "people").unwrap();
+    let people: Namespace<i32> = Namespace::open(&database, "people").unwrap();
+    assert_eq!(people.id(), users.id());
+    assert_eq!(people.name(), "people");
+    // open handles keep the name they were opened with
+    assert_eq!(users.name(), "users");
+    assert_eq!(people.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+    assert_eq!(
+        namespace::rename(&database, "people", "groups")
+            .err()
+            .unwrap()
+            .kind(),
+        ErrorKind::InvalidArgument
+    );
+
+    namespace::drop_namespace(&database, "people").unwrap();
+    assert_eq!(
+        namespace::list(&database).unwrap(),
+        vec![("groups".to_string(), groups.id())]
+    );
+    assert_eq!(
+        namespace::drop_namespace(&database, "people")
+            .err()
+            .unwrap()
+            .kind(),
+        ErrorKind::NotFound
+    );
+    let people: Namespace<i32> = Namespace::open(&database, "people").unwrap();
+    assert_ne!(people.id(), users.id());
+    assert_eq!(people.keys_iter(ReadOptions::new()).count(), 0);
+    assert_eq!(groups.get(ReadOptions::new(), 1).unwrap(), Some(vec![2]));
+}
+
+#[test]
+fn test_namespace_custom_comparator() {
+    let tmp = tmpdir("namespace_comparator");
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let database: Database<i32> =
+        Database::open_with_raw_compare_fn(tmp.path(), opts, "bytewise", |a, b| a.cmp(b)).unwrap();
+    assert_eq!(
+        Namespace::<i32>::open(&database, "users")
+            .err()
+            .unwrap()
+            .kind(),
+        ErrorKind::InvalidArgument
+    );
+}
@@ -0,0 +1,46 @@
+use crate::utils::{db_put_simple, open_database, tmpdir};
+use leveldb::compaction::Compaction;
+use leveldb::properties::Properties;
+
+#[test]
+fn test_unknown_property() {
+    let tmp = tmpdir("property_unknown");
+    let database = open_database::<i32>(tmp.path(), true);
+    assert_eq!(database.property("leveldb.unknown"), None);
+}
+
+#[test]
+fn test_typed_properties() {
+    let tmp = tmpdir("properties");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, 1, &[1]);
+    db_put_simple(database, 2, &[2]);
+    database.compact(&1, &2);
+
+    assert!(database.approximate_memory_usage().is_some());
+
+    let files: usize = (0..7)
+        .map(|level| database.num_files_at_level(level).unwrap())
+        .sum();
+    assert!(files > 0);
+
+    let stats = database.stats().unwrap();
+    let files_in_stats: usize = stats.levels.iter().map(|l| l.files).sum();
+    assert_eq!(files, files_in_stats);
+    assert_eq!(files, database.sstables().unwrap().len());
+}
+
+#[test]
+fn test_sstables_with_delimiter_in_key() {
+    let tmp = tmpdir("properties_sstables");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, "a .. b".to_string(), &[1]);
+    db_put_simple(database, "z".to_string(), &[2]);
+    database.compact_all();
+
+    let tables = database.sstables().unwrap();
+    assert!(tables
+        .iter()
+        .any(|table| table.smallest.starts_with("'a .. b' @ ")
+            && table.largest.starts_with("'z' @ ")));
+}
@@ -0,0 +1,106 @@
+use crate::utils::{open_database, tmpdir};
+use leveldb::batch::{Batch, Writebatch};
+use leveldb::comparator::{OrdComparator, ReverseBytewiseComparator};
+use leveldb::database::serializable::Serializable;
+use leveldb::database::Database;
+use leveldb::error::ErrorKind;
+use leveldb::iterator::Iterable;
+use leveldb::kv::KV;
+use leveldb::options::{Options, ReadOptions, WriteOptions};
+use leveldb::raw::RawDatabase;
+use leveldb::snapshots::Snapshots;
+
+fn open_raw(name: &str) -> (tempdir::TempDir, RawDatabase) {
+    let tmp = tmpdir(name);
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let database = RawDatabase::open(tmp.path(), opts).unwrap();
+    (tmp, database)
+}
+
+#[test]
+fn test_raw_kv() {
+    let (_tmp, database) = open_raw("raw_kv");
+    database.put(WriteOptions::new(), b"a", &[1]).unwrap();
+    assert_eq!(
+        database.get(ReadOptions::new(), b"a").unwrap(),
+        Some(vec![1])
+    );
+    database.delete(WriteOptions::new(), b"a").unwrap();
+    assert_eq!(database.get(ReadOptions::new(), b"a").unwrap(), None);
+
+    let mut batch = Writebatch::new();
+    batch.put(b"b".to_vec(), &[2]);
+    batch.put(b"c".to_vec(), &[3]);
+    database.write(WriteOptions::new(), &batch).unwrap();
+
+    let snapshot = database.snapshot();
+    database.put(WriteOptions::new(), b"d", &[4]).unwrap();
+    let keys: Vec<Vec<u8>> = snapshot.keys_iter(ReadOptions::new()).collect();
+    assert_eq!(keys, vec![b"b".to_vec(), b"c".to_vec()]);
+    let entries: Vec<(Vec<u8>, Vec<u8>)> = database.prefix_iter(ReadOptions::new(), b"d").collect();
+    assert_eq!(entries, vec![(b"d".to_vec(), vec![4])]);
+}
+
+#[test]
+fn test_raw_views() {
+    let (_tmp, database) = open_raw("raw_views");
+    let numbers: Database<u32> = database.view();
+    let names: Database<String> = database.view();
+    numbers.put(WriteOptions::new(), 7, &[1]).unwrap();
+    names
+        .put(WriteOptions::new(), "alice".to_string(), &[2])
+        .unwrap();
+
+    assert_eq!(
+        database
+            .get(ReadOptions::new(), &7u32.to_be_bytes())
+            .unwrap(),
+        Some(vec![1])
+    );
+    assert_eq!(
+        database.get(ReadOptions::new(), b"alice").unwrap(),
+        Some(vec![2])
+    );
+    let keys: Vec<u32> = numbers.range_keys(ReadOptions::new(), ..1 << 24).collect();
+    assert_eq!(keys, vec![7]);
+
+    // views keep the database open
+    drop(database);
+    assert_eq!(numbers.get(ReadOptions::new(), 7).unwrap(), Some(vec![1]));
+}
+
+#[test]
+fn test_database_raw() {
+    let tmp = tmpdir("database_raw");
+    let database: Database<i32> = open_database(tmp.path(), true);
+    database.put(WriteOptions::new(), 1, &[1]).unwrap();
+    let raw = database.raw().unwrap();
+    assert_eq!(
+        raw.get(ReadOptions::new(), &1i32.as_u8()).unwrap(),
+        Some(vec![1])
+    );
+    drop(database);
+    raw.put(WriteOptions::new(), b"x", &[2]).unwrap();
+    assert_eq!(raw.iter(ReadOptions::new()).count(), 2);
+}
+
+#[test]
+fn test_database_raw_typed_comparator() {
+    let tmp = tmpdir("database_raw_comparator");
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let comparator: OrdComparator<i32> = OrdComparator::new("ord");
+    let database = Database::open_with_comparator(tmp.path(), opts, comparator).unwrap();
+    assert_eq!(
+        database.raw().err().unwrap().kind(),
+        ErrorKind::InvalidArgument
+    );
+
+    let tmp = tmpdir("database_raw_raw_comparator");
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let database: Database<i32> =
+        Database::open_with_raw_comparator(tmp.path(), opts, ReverseBytewiseComparator).unwrap();
+    assert!(database.raw().is_ok());
+}
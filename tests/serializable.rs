@@ -0,0 +1,103 @@
+use crate::utils::{db_put_simple, open_database, tmpdir};
+use leveldb::database::serializable::{LegacyI32, Serializable};
+use leveldb::database::Database;
+use leveldb::iterator::Iterable;
+use leveldb::kv::KV;
+use leveldb::options::{Options, ReadOptions, WriteOptions};
+use std::fmt::Debug;
+
+fn assert_order_preserved<K: Serializable + Ord + Debug>(mut values: Vec<K>) {
+    values.sort();
+    for value in values.iter() {
+        assert_eq!(&K::from_u8(&value.as_u8()), value);
+    }
+    for pair in values.windows(2) {
+        assert!(pair[0].as_u8() <= pair[1].as_u8(), "{:?}", pair);
+    }
+}
+
+#[test]
+fn test_integers_preserve_order() {
+    assert_order_preserved(vec![0u8, 1, 127, 128, 255]);
+    assert_order_preserved(vec![0u16, 1, 256, u16::MAX]);
+    assert_order_preserved(vec![0u32, 1, 1 << 16, u32::MAX]);
+    assert_order_preserved(vec![0u64, 1, 1 << 40, u64::MAX]);
+    assert_order_preserved(vec![0u128, 1, 1 << 100, u128::MAX]);
+    assert_order_preserved(vec![i8::MIN, -1, 0, 1, i8::MAX]);
+    assert_order_preserved(vec![i16::MIN, -300, -1, 0, 1, 300, i16::MAX]);
+    assert_order_preserved(vec![i32::MIN, -70000, -1, 0, 1, 70000, i32::MAX]);
+    assert_order_preserved(vec![i64::MIN, -1, 0, 1, i64::MAX]);
+    assert_order_preserved(vec![i128::MIN, -1, 0, 1, i128::MAX]);
+}
+
+#[test]
+fn test_other_types_preserve_order() {
+    assert_order_preserved(vec![false, true]);
+    assert_order_preserved(vec!['\0', 'a', 'z', 'é', '\u{10FFFF}']);
+    assert_order_preserved(vec![
+        String::new(),
+        "a".to_string(),
+        "ab".to_string(),
+        "b".to_string(),
+        "é".to_string(),
+    ]);
+    assert_order_preserved(vec![vec![], vec![0u8], vec![0, 1], vec![1], vec![255]]);
+    assert_order_preserved(vec![[0u8, 0], [0, 1], [1, 0], [255, 255]]);
+}
+
+#[test]
+fn test_negative_keys_iterate_in_order() {
+    let tmp = tmpdir("negative_keys");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, 1, &[1]);
+    db_put_simple(database, -1, &[2]);
+    db_put_simple(database, 0, &[3]);
+
+    let keys: Vec<i32> = database.keys_iter(ReadOptions::new()).collect();
+    assert_eq!(keys, vec![-1, 0, 1]);
+}
+
+#[test]
+fn test_byte_keys() {
+    let tmp = tmpdir("byte_keys");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, b"b".to_vec(), &[1]);
+    db_put_simple(database, b"a".to_vec(), &[2]);
+
+    let keys: Vec<Vec<u8>> = database.keys_iter(ReadOptions::new()).collect();
+    assert_eq!(keys, vec![b"a".to_vec(), b"b".to_vec()]);
+}
+
+#[test]
+fn test_u64_keys() {
+    let tmp = tmpdir("u64_keys");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, 1u64 << 40, &[1]);
+    db_put_simple(database, 7u64, &[2]);
+
+    let keys: Vec<u64> = database.keys_iter(ReadOptions::new()).collect();
+    assert_eq!(keys, vec![7, 1 << 40]);
+}
+
+#[test]
+fn test_legacy_i32_keys() {
+    assert_eq!(LegacyI32(-2).as_u8(), vec![0xff, 0xff, 0xff, 0xfe]);
+    assert_eq!(LegacyI32::from_u8(&[0, 0, 1, 0]), LegacyI32(256));
+
+    let tmp = tmpdir("legacy_i32_keys");
+    let mut opts = Options::new();
+    opts.create_if_missing = true;
+    let database: Database<LegacyI32> = Database::open(tmp.path(), opts).unwrap();
+    database
+        .put(WriteOptions::new(), LegacyI32(1), &[1])
+        .unwrap();
+    database
+        .put(WriteOptions::new(), LegacyI32(-1), &[2])
+        .unwrap();
+
+    let keys: Vec<i32> = database
+        .keys_iter(ReadOptions::new())
+        .map(i32::from)
+        .collect();
+    assert_eq!(keys, vec![1, -1]);
+}
@@ -1,7 +1,8 @@
 use crate::utils::{db_put_simple, open_database, tmpdir};
 use leveldb::iterator::Iterable;
 use leveldb::options::ReadOptions;
-use leveldb::snapshots::Snapshots;
+use leveldb::snapshots::{OwnedSnapshot, Snapshots};
+use std::sync::Arc;
 
 #[test]
 fn test_snapshots() {
@@ -30,3 +31,33 @@ fn test_snapshot_iterator() {
     let next = iter.next();
     assert_eq!(None, next);
 }
+
+#[test]
+fn test_snapshot_range() {
+    let tmp = tmpdir("snap_range");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, 1, &[1]);
+    db_put_simple(database, 2, &[2]);
+    let snapshot = database.snapshot();
+    db_put_simple(database, 3, &[3]);
+    let read_opts = ReadOptions::new();
+    let keys: Vec<i32> = snapshot.range_keys(read_opts, 2..).collect();
+    assert_eq!(keys, vec![2]);
+}
+
+#[test]
+fn test_owned_snapshot() {
+    let tmp = tmpdir("owned_snapshot");
+    let database = Arc::new(open_database(tmp.path(), true));
+    db_put_simple(&database, 1, &[1]);
+    let snapshot = OwnedSnapshot::new(database.clone());
+    db_put_simple(&database, 2, &[2]);
+    drop(database);
+
+    assert_eq!(snapshot.get(ReadOptions::new(), 1).unwrap(), Some(vec![1]));
+    assert_eq!(snapshot.get(ReadOptions::new(), 2).unwrap(), None);
+    let iter = snapshot.iter(ReadOptions::new());
+    drop(snapshot);
+    let entries: Vec<(i32, Vec<u8>)> = iter.collect();
+    assert_eq!(entries, vec![(1, vec![1])]);
+}
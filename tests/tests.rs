@@ -2,14 +2,27 @@ extern crate leveldb;
 extern crate libc;
 extern crate tempdir;
 
+mod approximate_sizes;
+#[cfg(feature = "tokio")]
+mod async_database;
 mod binary;
 mod cache;
 mod compaction;
 mod comparator;
 mod concurrent_access;
 mod database;
+#[cfg(feature = "derive")]
+mod derive;
+mod filter_policy;
 mod iterator;
 mod management;
+mod namespace;
+mod properties;
+mod raw;
+mod serializable;
 mod snapshots;
+mod tuple;
+#[cfg(feature = "json")]
+mod typed;
 mod utils;
 mod writebatch;
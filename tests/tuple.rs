@@ -0,0 +1,72 @@
+use crate::utils::{db_put_simple, open_database, tmpdir};
+use leveldb::database::serializable::Serializable;
+use leveldb::database::tuple::prefix_range;
+use leveldb::iterator::{Iterable, LevelDBIterator};
+use leveldb::options::ReadOptions;
+
+#[test]
+fn test_tuple_roundtrip() {
+    let key = (
+        7u32,
+        "a\0b".to_string(),
+        vec![0u8, 255],
+        -3i64,
+        true,
+        'x',
+        [0u8, 1],
+        u128::MAX,
+    );
+    let decoded: (u32, String, Vec<u8>, i64, bool, char, [u8; 2], u128) =
+        Serializable::from_u8(&key.as_u8());
+    assert_eq!(decoded, key);
+}
+
+#[test]
+fn test_tuple_order_with_variable_length_first() {
+    let mut keys = vec![
+        ("ab".to_string(), 1u8),
+        ("a".to_string(), 255u8),
+        ("a\0".to_string(), 0u8),
+        ("".to_string(), 9u8),
+        ("b".to_string(), 0u8),
+    ];
+    keys.sort();
+    let mut encoded: Vec<Vec<u8>> = keys.iter().map(|k| k.as_u8()).collect();
+    encoded.sort();
+    let decoded: Vec<(String, u8)> = encoded.iter().map(|k| <(String, u8)>::from_u8(k)).collect();
+    assert_eq!(decoded, keys);
+}
+
+#[test]
+fn test_prefix_range() {
+    let (from, to) = prefix_range::<(u32, String, u64), _>(&(7u32, "users".to_string()));
+    assert_eq!(from, (7, "users".to_string(), 0));
+    assert_eq!(to, Some((7, "users\0".to_string(), 0)));
+
+    let (from, to) = prefix_range::<(u8, bool, u8), _>(&(3u8, true));
+    assert_eq!(from, (3, true, 0));
+    assert_eq!(to, Some((4, false, 0)));
+
+    let (_, to) = prefix_range::<(u8, u8), _>(&(255u8,));
+    assert_eq!(to, None);
+}
+
+#[test]
+fn test_prefix_range_iteration() {
+    let tmp = tmpdir("tuple_prefix");
+    let database = &mut open_database(tmp.path(), true);
+    db_put_simple(database, (1u32, "orders".to_string(), 1u64), &[1]);
+    db_put_simple(database, (1u32, "users".to_string(), 1u64), &[2]);
+    db_put_simple(database, (1u32, "users".to_string(), 2u64), &[3]);
+    db_put_simple(database, (1u32, "users2".to_string(), 1u64), &[4]);
+    db_put_simple(database, (2u32, "users".to_string(), 1u64), &[5]);
+
+    let (from, to) = prefix_range(&(1u32, "users".to_string()));
+    let to = to.unwrap();
+    let values: Vec<Vec<u8>> = database
+        .value_iter(ReadOptions::new())
+        .from(&from)
+        .to(&to)
+        .collect();
+    assert_eq!(values, vec![vec![2], vec![3]]);
+}
@@ -0,0 +1,69 @@
+use crate::utils::{db_put_simple, open_database, tmpdir};
+use leveldb::error::ErrorKind;
+use leveldb::options::{ReadOptions, WriteOptions};
+use leveldb::typed::{Json, Raw, TypedDatabase};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct User {
+    name: String,
+    age: u32,
+}
+
+#[test]
+fn test_typed_json() {
+    let tmp = tmpdir("typed_json");
+    let database = open_database(tmp.path(), true);
+    let users: TypedDatabase<i32, User, Json> = TypedDatabase::new(&database);
+
+    let alice = User {
+        name: "alice".to_string(),
+        age: 30,
+    };
+    let bob = User {
+        name: "bob".to_string(),
+        age: 40,
+    };
+    users.put(WriteOptions::new(), 1, &alice).unwrap();
+    users.put(WriteOptions::new(), 2, &bob).unwrap();
+    assert_eq!(users.get(ReadOptions::new(), 1).unwrap(), Some(alice));
+    assert_eq!(users.get(ReadOptions::new(), 3).unwrap(), None);
+
+    let names: Vec<String> = users
+        .iter(ReadOptions::new())
+        .reverse()
+        .map(|entry| entry.unwrap().1.name)
+        .collect();
+    assert_eq!(names, vec!["bob", "alice"]);
+    users.delete(WriteOptions::new(), 2).unwrap();
+    assert_eq!(users.range(ReadOptions::new(), 2..).count(), 0);
+}
+
+#[test]
+fn test_typed_decode_error() {
+    let tmp = tmpdir("typed_decode_error");
+    let database = open_database(tmp.path(), true);
+    db_put_simple(&database, 1, b"{\"name\":\"carol\",\"age\":20}");
+    db_put_simple(&database, 2, b"not json");
+    let users: TypedDatabase<i32, User, Json> = TypedDatabase::new(&database);
+
+    let err = users.get(ReadOptions::new(), 2).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Corruption);
+
+    let mut iter = users.iter(ReadOptions::new());
+    assert_eq!(iter.next().unwrap().unwrap().1.name, "carol");
+    assert_eq!(
+        iter.next().unwrap().unwrap_err().kind(),
+        ErrorKind::Corruption
+    );
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_typed_raw() {
+    let tmp = tmpdir("typed_raw");
+    let database = open_database(tmp.path(), true);
+    let raw: TypedDatabase<i32, Vec<u8>, Raw> = TypedDatabase::new(&database);
+    raw.put(WriteOptions::new(), 1, &vec![1, 2]).unwrap();
+    assert_eq!(raw.get(ReadOptions::new(), 1).unwrap(), Some(vec![1, 2]));
+}
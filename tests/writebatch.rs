@@ -1,7 +1,9 @@
 use crate::utils::tmpdir;
 use leveldb::database::batch::{Batch, Writebatch, WritebatchIterator};
 use leveldb::database::kv::KV;
+use leveldb::database::serializable::Serializable;
 use leveldb::database::Database;
+use leveldb::error::{Error, ErrorKind};
 use leveldb::options::{Options, ReadOptions, WriteOptions};
 
 #[test]
@@ -77,7 +79,124 @@ fn test_writebatchiter() {
     assert!(ack.is_ok());
 
     let iter = Box::new(Iter { put: 0, deleted: 0 });
-    let iter2 = batch.iterate(iter);
+    let iter2 = batch.iterate(iter).unwrap();
     assert_eq!(iter2.put, 2);
     assert_eq!(iter2.deleted, 1);
 }
+
+#[derive(Debug, PartialEq)]
+struct EvenKey(u8);
+
+impl Serializable for EvenKey {
+    fn from_u8(key: &[u8]) -> EvenKey {
+        EvenKey::try_from_u8(key).unwrap()
+    }
+
+    fn as_u8(&self) -> Vec<u8> {
+        vec![self.0]
+    }
+
+    fn try_from_u8(key: &[u8]) -> Result<EvenKey, Error> {
+        match key {
+            [b] if b % 2 == 0 => Ok(EvenKey(*b)),
+            _ => Err(Error::with_kind(
+                ErrorKind::Corruption,
+                "odd key".to_string(),
+            )),
+        }
+    }
+}
+
+struct EvenIter {
+    keys: Vec<u8>,
+}
+
+impl WritebatchIterator for EvenIter {
+    type K = EvenKey;
+
+    fn put(&mut self, key: EvenKey, _value: &[u8]) {
+        self.keys.push(key.0);
+    }
+
+    fn deleted(&mut self, key: EvenKey) {
+        self.keys.push(key.0);
+    }
+}
+
+#[test]
+fn test_writebatchiter_malformed_key() {
+    let batch = &mut Writebatch::new();
+    batch.put(EvenKey(2), &[2]);
+    batch.delete(EvenKey(3));
+    batch.put(EvenKey(4), &[4]);
+
+    let res = batch.iterate(Box::new(EvenIter { keys: vec![] }));
+    assert_eq!(res.err().unwrap().kind(), ErrorKind::Corruption);
+
+    let batch = &mut Writebatch::new();
+    batch.put(EvenKey(2), &[2]);
+    batch.delete(EvenKey(4));
+    let res = batch.iterate(Box::new(EvenIter { keys: vec![] }));
+    assert_eq!(res.unwrap().keys, vec![2, 4]);
+}
+
+struct PanickingIter;
+
+impl WritebatchIterator for PanickingIter {
+    type K = i32;
+
+    fn put(&mut self, _key: i32, _value: &[u8]) {
+        panic!("put");
+    }
+
+    fn deleted(&mut self, _key: i32) {}
+}
+
+#[test]
+fn test_writebatchiter_panic() {
+    let batch = &mut Writebatch::new();
+    batch.put(1, &[1]);
+    batch.put(2, &[2]);
+
+    let res = batch.iterate(Box::new(PanickingIter));
+    assert_eq!(res.err().unwrap().kind(), ErrorKind::Poisoned);
+}
+
+struct PanickingKey;
+
+impl Serializable for PanickingKey {
+    fn from_u8(_key: &[u8]) -> PanickingKey {
+        panic!("from_u8");
+    }
+
+    fn as_u8(&self) -> Vec<u8> {
+        vec![0]
+    }
+
+    fn try_from_u8(_key: &[u8]) -> Result<PanickingKey, Error> {
+        panic!("try_from_u8");
+    }
+}
+
+struct PanickingKeyIter;
+
+impl WritebatchIterator for PanickingKeyIter {
+    type K = PanickingKey;
+
+    fn put(&mut self, _key: PanickingKey, _value: &[u8]) {}
+
+    fn deleted(&mut self, _key: PanickingKey) {}
+}
+
+#[test]
+fn test_writebatchiter_panicking_key() {
+    let batch = &mut Writebatch::new();
+    batch.put(PanickingKey, &[1]);
+    let res = batch.iterate(Box::new(PanickingKeyIter));
+    assert_eq!(res.err().unwrap().kind(), ErrorKind::Poisoned);
+
+    let batch = &mut Writebatch::new();
+    batch.delete(PanickingKey);
+    let res = batch.iterate(Box::new(PanickingKeyIter));
+    assert_eq!(res.err().unwrap().kind(), ErrorKind::Poisoned);
+}